@@ -1,23 +1,65 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Symbol, U256};
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Symbol, U256};
 
 #[contract]
 pub struct GrantContract;
 
+// Every stored grant setting, as moved between contract versions by export_state / import_state
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrantState {
+    pub recipient: Address,
+    pub admin: Address,
+    pub total_amount: U256,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub claimed: U256,
+    pub cliff_only: bool,
+    pub curve: u32,
+    pub vested_cap: Option<U256>, // set once the grant is revoked
+}
+
 const TOTAL_AMOUNT: Symbol = symbol_short!("TOTAL");
 const START_TIME: Symbol = symbol_short!("START");
 const END_TIME: Symbol = symbol_short!("END");
 const RECIPIENT: Symbol = symbol_short!("RECIPIENT");
 const CLAIMED: Symbol = symbol_short!("CLAIMED");
+const CLIFF_ONLY: Symbol = symbol_short!("CLIFF");
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const CURVE: Symbol = symbol_short!("CURVE");
+const VESTED_CAP: Symbol = symbol_short!("CAP");
+
+// Vesting curves selectable at initialize_grant
+const CURVE_LINEAR: u32 = 0;
+const CURVE_QUADRATIC: u32 = 1;
+
+const CONTRACT_VERSION: (u32, u32, u32) = (1, 0, 0);
+const CONTRACT_TYPE: Symbol = symbol_short!("grant");
 
 #[contractimpl]
+#[allow(deprecated)]
 impl GrantContract {
+    // Takes the full grant configuration as contract arguments
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_grant(
         env: Env,
+        admin: Address,
         recipient: Address,
         total_amount: U256,
         duration_seconds: u64,
+        cliff_only: bool,
+        require_recipient_ack: bool,
+        curve: u32,
     ) -> u64 {
+        assert!(!env.storage().instance().has(&RECIPIENT), "Grant already initialized");
+        assert!(curve == CURVE_LINEAR || curve == CURVE_QUADRATIC, "Unsupported curve");
+        admin.require_auth();
+        
+        // Optionally have the recipient co-sign creation of their grant
+        if require_recipient_ack {
+            recipient.require_auth();
+        }
+        
         let start_time = env.ledger().timestamp();
         let end_time = start_time + duration_seconds;
         
@@ -26,43 +68,119 @@ impl GrantContract {
         env.storage().instance().set(&END_TIME, &end_time);
         env.storage().instance().set(&RECIPIENT, &recipient);
         env.storage().instance().set(&CLAIMED, &U256::from_u32(&env, 0));
+        env.storage().instance().set(&CLIFF_ONLY, &cliff_only);
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&CURVE, &curve);
         
         end_time
     }
     
+    // Top up the grant. The schedule is re-based on the new total: vesting is recomputed as
+    // new_total * elapsed / duration, so the already-elapsed share of `extra` unlocks immediately
+    // and the rest vests with the original schedule. Claimed amounts are untouched.
+    pub fn increase_grant(env: Env, extra: U256) {
+        let admin: Address = env.storage().instance().get(&ADMIN).expect("Admin not set");
+        admin.require_auth();
+        assert!(!env.storage().instance().has(&VESTED_CAP), "Grant revoked");
+        assert!(extra > U256::from_u32(&env, 0), "Top-up must be positive");
+        
+        let total_amount: U256 = env.storage().instance().get(&TOTAL_AMOUNT).unwrap_or(U256::from_u32(&env, 0));
+        let new_total = total_amount.add(&extra);
+        env.storage().instance().set(&TOTAL_AMOUNT, &new_total);
+        
+        env.events().publish(
+            (Symbol::new(&env, "GrantIncreased"),),
+            (extra, new_total),
+        );
+    }
+    
     pub fn claimable_balance(env: Env) -> U256 {
-        let current_time = env.ledger().timestamp();
+        let now = env.ledger().timestamp();
+        Self::claimable_balance_at(env, now)
+    }
+
+    // Projected claimable amount at an arbitrary timestamp, given what has been claimed so far
+    pub fn claimable_balance_at(env: Env, at: u64) -> U256 {
         let start_time = env.storage().instance().get(&START_TIME).unwrap_or(0);
         let end_time = env.storage().instance().get(&END_TIME).unwrap_or(0);
         let total_amount = env.storage().instance().get(&TOTAL_AMOUNT).unwrap_or(U256::from_u32(&env, 0));
         let claimed = env.storage().instance().get(&CLAIMED).unwrap_or(U256::from_u32(&env, 0));
         
-        if current_time <= start_time {
+        if at <= start_time {
             return U256::from_u32(&env, 0);
         }
         
-        let elapsed = if current_time >= end_time {
+        // Cliff-only grants unlock nothing until end_time, then everything at once
+        let cliff_only: bool = env.storage().instance().get(&CLIFF_ONLY).unwrap_or(false);
+        if cliff_only {
+            let vested = if at < end_time {
+                U256::from_u32(&env, 0)
+            } else {
+                total_amount
+            };
+            return Self::unclaimed(&env, vested, claimed);
+        }
+        
+        let elapsed = if at >= end_time {
             end_time - start_time
         } else {
-            current_time - start_time
+            at - start_time
         };
         
         let total_duration = end_time - start_time;
         let vested = if total_duration > 0 {
             let elapsed_u256 = U256::from_u32(&env, elapsed as u32);
             let duration_u256 = U256::from_u32(&env, total_duration as u32);
-            total_amount.mul(&elapsed_u256).div(&duration_u256)
+            let curve: u32 = env.storage().instance().get(&CURVE).unwrap_or(CURVE_LINEAR);
+            if curve == CURVE_QUADRATIC {
+                // Back-weighted: total * (elapsed / duration)^2
+                total_amount
+                    .mul(&elapsed_u256)
+                    .mul(&elapsed_u256)
+                    .div(&duration_u256.mul(&duration_u256))
+            } else {
+                total_amount.mul(&elapsed_u256).div(&duration_u256)
+            }
         } else {
             U256::from_u32(&env, 0)
         };
         
+        Self::unclaimed(&env, vested, claimed)
+    }
+    
+    // Vested minus claimed, with vesting frozen at the revocation cap once the grant is revoked
+    fn unclaimed(env: &Env, vested: U256, claimed: U256) -> U256 {
+        let vested = match env.storage().instance().get::<Symbol, U256>(&VESTED_CAP) {
+            Some(cap) if cap < vested => cap,
+            _ => vested,
+        };
+        
         if vested > claimed {
             vested.sub(&claimed)
         } else {
-            U256::from_u32(&env, 0)
+            U256::from_u32(env, 0)
         }
     }
     
+    // Stop further vesting. The recipient keeps whatever had vested at revocation time and can
+    // still claim it; the rest of the grant is forfeited. Returns (vested, forfeited).
+    pub fn revoke_grant(env: Env) -> (U256, U256) {
+        let admin: Address = env.storage().instance().get(&ADMIN).expect("Admin not set");
+        admin.require_auth();
+        assert!(!env.storage().instance().has(&VESTED_CAP), "Grant already revoked");
+        
+        let (total_amount, _claimed, vested, _claimable) = Self::status(env.clone());
+        let forfeited = total_amount.sub(&vested);
+        env.storage().instance().set(&VESTED_CAP, &vested);
+        
+        env.events().publish(
+            (Symbol::new(&env, "GrantRevoked"),),
+            (vested.clone(), forfeited.clone()),
+        );
+        
+        (vested, forfeited)
+    }
+    
     pub fn claim(env: Env, recipient: Address) -> U256 {
         recipient.require_auth();
         
@@ -87,6 +205,62 @@ impl GrantContract {
         
         (total_amount, start_time, end_time, claimed)
     }
+    
+    // Snapshot as (total, claimed, vested_now, claimable_now), the same shape as VestingVault::status
+    pub fn status(env: Env) -> (U256, U256, U256, U256) {
+        let (total_amount, _start_time, _end_time, claimed) = Self::get_grant_info(env.clone());
+        let claimable = Self::claimable_balance(env);
+        let vested = claimed.add(&claimable);
+        
+        (total_amount, claimed, vested, claimable)
+    }
+    
+    pub fn version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+    
+    pub fn contract_type(_env: Env) -> Symbol {
+        CONTRACT_TYPE
+    }
+    
+    // Export the full grant state for migration to a new version
+    pub fn export_state(env: Env) -> GrantState {
+        let (total_amount, start_time, end_time, claimed) = Self::get_grant_info(env.clone());
+        
+        GrantState {
+            recipient: env.storage().instance().get(&RECIPIENT).unwrap(),
+            admin: env.storage().instance().get(&ADMIN).expect("Admin not set"),
+            total_amount,
+            start_time,
+            end_time,
+            claimed,
+            cliff_only: env.storage().instance().get(&CLIFF_ONLY).unwrap_or(false),
+            curve: env.storage().instance().get(&CURVE).unwrap_or(CURVE_LINEAR),
+            vested_cap: env.storage().instance().get(&VESTED_CAP),
+        }
+    }
+    
+    // Rebuild an exported grant on a fresh contract; refuses to overwrite an existing grant.
+    // The grant's admin must authorize the import.
+    pub fn import_state(env: Env, state: GrantState) {
+        assert!(!env.storage().instance().has(&RECIPIENT), "Grant already initialized");
+        state.admin.require_auth();
+        assert!(state.end_time >= state.start_time, "Invalid grant schedule");
+        assert!(state.claimed <= state.total_amount, "Claimed exceeds total");
+        assert!(state.curve == CURVE_LINEAR || state.curve == CURVE_QUADRATIC, "Unsupported curve");
+        
+        env.storage().instance().set(&TOTAL_AMOUNT, &state.total_amount);
+        env.storage().instance().set(&START_TIME, &state.start_time);
+        env.storage().instance().set(&END_TIME, &state.end_time);
+        env.storage().instance().set(&RECIPIENT, &state.recipient);
+        env.storage().instance().set(&CLAIMED, &state.claimed);
+        env.storage().instance().set(&CLIFF_ONLY, &state.cliff_only);
+        env.storage().instance().set(&ADMIN, &state.admin);
+        env.storage().instance().set(&CURVE, &state.curve);
+        if let Some(cap) = state.vested_cap {
+            env.storage().instance().set(&VESTED_CAP, &cap);
+        }
+    }
 }
 
 mod test;
@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Env, U256};
+use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Env, Symbol, U256};
 
 #[test]
 fn test_basic_grant() {
@@ -14,8 +14,424 @@ fn test_basic_grant() {
     let total_amount = U256::from_u32(&env, 1000);
     let duration = 100u64;
 
-    client.initialize_grant(&recipient, &total_amount, &duration);
+    client.initialize_grant(&Address::generate(&env), &recipient, &total_amount, &duration, &false, &false, &0u32);
     
     let claimable = client.claimable_balance();
     assert_eq!(claimable, U256::from_u32(&env, 0));
 }
+
+#[test]
+fn test_cliff_only_grant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    let total_amount = U256::from_u32(&env, 1000);
+    let duration = 100u64;
+
+    let start = env.ledger().timestamp();
+    client.initialize_grant(&Address::generate(&env), &recipient, &total_amount, &duration, &true, &false, &0u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 99);
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 0));
+
+    env.ledger().with_mut(|l| l.timestamp = start + duration);
+    assert_eq!(client.claimable_balance(), total_amount);
+}
+
+#[test]
+fn test_export_import_state_round_trip() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let old_id = env.register(GrantContract, ());
+    let old_client = GrantContractClient::new(&env, &old_id);
+
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    old_client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 30);
+    old_client.claim(&recipient);
+    env.ledger().with_mut(|l| l.timestamp = start + 50);
+
+    let state = old_client.export_state();
+    let new_id = env.register(GrantContract, ());
+    let new_client = GrantContractClient::new(&env, &new_id);
+    new_client.import_state(&state);
+
+    assert_eq!(new_client.export_state(), state);
+    assert_eq!(new_client.get_grant_info(), old_client.get_grant_info());
+    assert_eq!(new_client.claimable_balance(), old_client.claimable_balance());
+    assert_eq!(new_client.claimable_balance(), U256::from_u32(&env, 200));
+}
+
+#[test]
+fn test_export_import_keeps_quadratic_curve() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let old_id = env.register(GrantContract, ());
+    let old_client = GrantContractClient::new(&env, &old_id);
+
+    let start = env.ledger().timestamp();
+    old_client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &1u32);
+
+    let state = old_client.export_state();
+    assert_eq!(state.curve, 1u32);
+    let new_id = env.register(GrantContract, ());
+    let new_client = GrantContractClient::new(&env, &new_id);
+    new_client.import_state(&state);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 50);
+    assert_eq!(new_client.claimable_balance(), U256::from_u32(&env, 250));
+}
+
+#[test]
+#[should_panic(expected = "Grant already initialized")]
+fn test_import_state_rejects_initialized_grant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    let state = client.export_state();
+    client.import_state(&state);
+}
+
+#[test]
+fn test_export_import_keeps_cliff_and_revocation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let old_id = env.register(GrantContract, ());
+    let old_client = GrantContractClient::new(&env, &old_id);
+
+    let admin = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    old_client.initialize_grant(&admin, &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &true, &false, &0u32);
+    env.ledger().with_mut(|l| l.timestamp = start + 100);
+    old_client.revoke_grant();
+
+    let state = old_client.export_state();
+    assert!(state.cliff_only);
+    assert_eq!(state.admin, admin);
+    assert_eq!(state.vested_cap, Some(U256::from_u32(&env, 1000)));
+
+    let new_id = env.register(GrantContract, ());
+    let new_client = GrantContractClient::new(&env, &new_id);
+    new_client.import_state(&state);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(new_client.export_state(), state);
+    assert_eq!(new_client.claimable_balance(), U256::from_u32(&env, 1000));
+}
+
+#[test]
+#[should_panic(expected = "Claimed exceeds total")]
+fn test_import_state_rejects_claimed_over_total() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let old_id = env.register(GrantContract, ());
+    let old_client = GrantContractClient::new(&env, &old_id);
+    old_client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    let mut state = old_client.export_state();
+    state.claimed = U256::from_u32(&env, 1001);
+    let new_id = env.register(GrantContract, ());
+    GrantContractClient::new(&env, &new_id).import_state(&state);
+}
+
+#[test]
+#[should_panic]
+fn test_import_state_requires_admin_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let old_id = env.register(GrantContract, ());
+    let old_client = GrantContractClient::new(&env, &old_id);
+    old_client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    let state = old_client.export_state();
+
+    let new_id = env.register(GrantContract, ());
+    let new_client = GrantContractClient::new(&env, &new_id);
+    env.set_auths(&[]);
+    new_client.import_state(&state);
+}
+
+#[test]
+fn test_version_and_contract_type() {
+    let env = Env::default();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.contract_type(), Symbol::new(&env, "grant"));
+    assert_eq!(client.version(), (1, 0, 0));
+}
+
+#[test]
+fn test_increase_grant_raises_claimable() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    client.initialize_grant(&admin, &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 50);
+    client.claim(&recipient);
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 0));
+
+    client.increase_grant(&U256::from_u32(&env, 1000));
+    let (total, s, e, claimed) = client.get_grant_info();
+    assert_eq!(total, U256::from_u32(&env, 2000));
+    assert_eq!((s, e), (start, start + 100));
+    assert_eq!(claimed, U256::from_u32(&env, 500));
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 500));
+
+    env.ledger().with_mut(|l| l.timestamp = start + 100);
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 1500));
+}
+
+#[test]
+fn test_increase_grant_emits_event() {
+    use soroban_sdk::testutils::Events;
+    use soroban_sdk::{vec, IntoVal};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    client.increase_grant(&U256::from_u32(&env, 250));
+    assert_eq!(
+        env.events().all().filter_by_contract(&contract_id),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (Symbol::new(&env, "GrantIncreased"),).into_val(&env),
+                (U256::from_u32(&env, 250), U256::from_u32(&env, 1250)).into_val(&env),
+            ),
+        ]
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_increase_grant_rejects_non_admin() {
+    use soroban_sdk::testutils::{MockAuth, MockAuthInvoke};
+    use soroban_sdk::IntoVal;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    // Only the recipient signs; increase_grant requires the admin.
+    let extra = U256::from_u32(&env, 1000);
+    client
+        .mock_auths(&[MockAuth {
+            address: &recipient,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "increase_grant",
+                args: (extra.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .increase_grant(&extra);
+}
+
+#[test]
+#[should_panic]
+fn test_recipient_ack_required_without_auth() {
+    let env = Env::default();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &true, &0u32);
+}
+
+#[test]
+fn test_recipient_ack_with_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    client.initialize_grant(&admin, &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &true, &0u32);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(env.auths()[1].0, recipient);
+}
+
+#[test]
+fn test_initialize_grant_requires_admin_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize_grant(&admin, &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    assert_eq!(env.auths()[0].0, admin);
+}
+
+#[test]
+#[should_panic(expected = "Grant already initialized")]
+fn test_initialize_grant_rejects_reinitialization() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+}
+
+#[test]
+fn test_quadratic_curve_is_back_weighted() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let linear_id = env.register(GrantContract, ());
+    let linear = GrantContractClient::new(&env, &linear_id);
+    let quadratic_id = env.register(GrantContract, ());
+    let quadratic = GrantContractClient::new(&env, &quadratic_id);
+
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    linear.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    quadratic.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &1u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 50);
+    assert_eq!(linear.claimable_balance(), U256::from_u32(&env, 500));
+    assert_eq!(quadratic.claimable_balance(), U256::from_u32(&env, 250));
+    assert!(quadratic.claimable_balance() < linear.claimable_balance());
+
+    env.ledger().with_mut(|l| l.timestamp = start + 100);
+    assert_eq!(quadratic.claimable_balance(), U256::from_u32(&env, 1000));
+}
+
+#[test]
+#[should_panic(expected = "Unsupported curve")]
+fn test_unknown_curve_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &2u32);
+}
+
+#[test]
+fn test_claimable_balance_at_projection() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    assert_eq!(client.claimable_balance_at(&start), U256::from_u32(&env, 0));
+    assert_eq!(client.claimable_balance_at(&(start + 50)), U256::from_u32(&env, 500));
+    assert_eq!(client.claimable_balance_at(&(start + 100)), U256::from_u32(&env, 1000));
+    assert_eq!(client.claimable_balance_at(&(start + 1_000)), U256::from_u32(&env, 1000));
+
+    // Projection does not move the ledger
+    assert_eq!(env.ledger().timestamp(), start);
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 0));
+}
+
+#[test]
+fn test_claimable_balance_at_subtracts_claimed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 30);
+    client.claim(&recipient);
+
+    assert_eq!(client.claimable_balance_at(&(start + 50)), U256::from_u32(&env, 200));
+    assert_eq!(client.claimable_balance_at(&(start + 100)), U256::from_u32(&env, 700));
+}
+
+#[test]
+fn test_status_at_halfway() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 30);
+    client.claim(&recipient);
+    env.ledger().with_mut(|l| l.timestamp = start + 50);
+
+    let (total, claimed, vested, claimable) = client.status();
+    assert_eq!(total, U256::from_u32(&env, 1000));
+    assert_eq!(claimed, U256::from_u32(&env, 300));
+    assert_eq!(vested, U256::from_u32(&env, 500));
+    assert_eq!(claimable, client.claimable_balance());
+    assert_eq!(claimed.add(&claimable), vested);
+}
+
+#[test]
+fn test_revoke_grant_freezes_vesting() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    client.initialize_grant(&Address::generate(&env), &recipient, &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+
+    env.ledger().with_mut(|l| l.timestamp = start + 20);
+    client.claim(&recipient);
+    env.ledger().with_mut(|l| l.timestamp = start + 40);
+
+    let (vested, forfeited) = client.revoke_grant();
+    assert_eq!(vested, U256::from_u32(&env, 400));
+    assert_eq!(forfeited, U256::from_u32(&env, 600));
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 200));
+
+    env.ledger().with_mut(|l| l.timestamp = start + 80);
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 200));
+    assert_eq!(client.claimable_balance_at(&(start + 1_000)), U256::from_u32(&env, 200));
+
+    client.claim(&recipient);
+    env.ledger().with_mut(|l| l.timestamp = start + 100);
+    assert_eq!(client.claimable_balance(), U256::from_u32(&env, 0));
+}
+
+#[test]
+#[should_panic(expected = "Grant already revoked")]
+fn test_revoke_grant_twice_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(GrantContract, ());
+    let client = GrantContractClient::new(&env, &contract_id);
+
+    client.initialize_grant(&Address::generate(&env), &Address::generate(&env), &U256::from_u32(&env, 1000), &100u64, &false, &false, &0u32);
+    client.revoke_grant();
+    client.revoke_grant();
+}
@@ -1,5 +1,6 @@
 use soroban_sdk::{
-    contract, contractimpl, contractmeta, contracttype, Address, BytesN, Env, Map, Vec,
+    contract, contractimpl, contractmeta, contracttype, vec, xdr::ToXdr, Address, Bytes, BytesN,
+    Env, IntoVal, Map, Symbol, Vec,
 };
 
 // Contract metadata for the factory
@@ -31,15 +32,18 @@ impl VestingFactory {
             .set(&DataKey::DeployedContracts, &deployed_contracts);
     }
 
-    /// Deploy a new vesting contract for an organization
-    /// Only allows deployment if token is whitelisted
-    pub fn deploy_new_vault_contract(
+    /// Deploy and initialize a vesting contract at a deterministic address derived from
+    /// the admin and `salt`. The address can be checked beforehand with `predict_vault_address`.
+    /// `kyc_required` is forwarded to the new contract's `initialize`.
+    pub fn deploy_vault_contract_with_salt(
         env: Env,
         admin: Address,
         initial_supply: i128,
         token: Address,
+        kyc_required: bool,
+        salt: BytesN<32>,
     ) -> Address {
-        let _wasm_hash: BytesN<32> = env
+        let wasm_hash: BytesN<32> = env
             .storage()
             .instance()
             .get(&DataKey::WasmHash)
@@ -55,8 +59,46 @@ impl VestingFactory {
             panic!("Token not whitelisted");
         }
 
-        let _ = (admin, initial_supply);
-        panic!("Factory deployment is not implemented for this soroban-sdk version");
+        let vault_salt = Self::vault_salt(&env, &admin, &salt);
+        let deployed = env
+            .deployer()
+            .with_current_contract(vault_salt)
+            .deploy_v2(wasm_hash, ());
+
+        // Initialize in the same transaction so nobody can claim the fresh contract first
+        let args = vec![
+            &env,
+            admin.into_val(&env),
+            initial_supply.into_val(&env),
+            true.into_val(&env),
+            kyc_required.into_val(&env),
+        ];
+        env.invoke_contract::<()>(&deployed, &Symbol::new(&env, "initialize"), args);
+
+        let mut deployed_contracts: Vec<Address> = Self::get_deployed_contracts(env.clone());
+        deployed_contracts.push_back(deployed.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::DeployedContracts, &deployed_contracts);
+
+        deployed
+    }
+
+    /// Predict the address `deploy_vault_contract_with_salt` would produce for `admin`
+    /// and `salt`, without deploying anything
+    pub fn predict_vault_address(env: Env, admin: Address, salt: BytesN<32>) -> Address {
+        let vault_salt = Self::vault_salt(&env, &admin, &salt);
+        env.deployer()
+            .with_current_contract(vault_salt)
+            .deployed_address()
+    }
+
+    /// Helper function to bind the deployment salt to the admin, so an address
+    /// derived for one admin cannot be reproduced for another
+    fn vault_salt(env: &Env, admin: &Address, salt: &BytesN<32>) -> BytesN<32> {
+        let mut preimage: Bytes = admin.clone().to_xdr(env);
+        preimage.append(&salt.clone().into());
+        env.crypto().sha256(&preimage).into()
     }
 
     /// Get all deployed contract addresses
@@ -83,4 +125,76 @@ impl VestingFactory {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_predicted_address_is_bound_to_admin_and_salt() {
+        let env = Env::default();
+        let factory_id = env.register(VestingFactory, ());
+        let client = VestingFactoryClient::new(&env, &factory_id);
+
+        let admin = Address::generate(&env);
+        let other_admin = Address::generate(&env);
+        let salt = BytesN::from_array(&env, &[1u8; 32]);
+        let other_salt = BytesN::from_array(&env, &[2u8; 32]);
+
+        let predicted = client.predict_vault_address(&admin, &salt);
+        assert_eq!(predicted, client.predict_vault_address(&admin, &salt));
+        assert_ne!(predicted, client.predict_vault_address(&other_admin, &salt));
+        assert_ne!(predicted, client.predict_vault_address(&admin, &other_salt));
+    }
+
+    /// Smallest contract the host accepts: exports a no-op `initialize(admin, supply, bool, bool)`
+    /// and carries the `contractenvmetav0` section, so the factory can deploy and initialize
+    /// it without a wasm32 build of the vesting contract.
+    const STUB_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+        0x01, 0x09, 0x01, 0x60, 0x04, 0x7e, 0x7e, 0x7e, 0x7e, 0x01, 0x7e, // (i64 x4) -> i64
+        0x03, 0x02, 0x01, 0x00, // one function of that type
+        0x07, 0x0e, 0x01, 0x0a, b'i', b'n', b'i', b't', b'i', b'a', b'l', b'i', b'z', b'e', 0x00,
+        0x00, // export "initialize"
+        0x0a, 0x06, 0x01, 0x04, 0x00, 0x42, 0x02, 0x0b, // body: return Void
+        0x00, 0x1e, 0x11, b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm',
+        b'e', b't', b'a', b'v', b'0', // custom section "contractenvmetav0"
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x19, 0x00, 0x00, 0x00, 0x00, // protocol 25
+    ];
+
+    #[test]
+    fn test_predicted_address_matches_deployment() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let wasm_hash = env
+            .deployer()
+            .upload_contract_wasm(Bytes::from_slice(&env, STUB_WASM));
+
+        let factory_id = env.register(VestingFactory, ());
+        let client = VestingFactoryClient::new(&env, &factory_id);
+        client.initialize_factory(&wasm_hash);
+
+        let token = Address::generate(&env);
+        env.as_contract(&factory_id, || {
+            let mut whitelist: Map<Address, bool> = Map::new(&env);
+            whitelist.set(token.clone(), true);
+            env.storage()
+                .instance()
+                .set(&crate::WhitelistDataKey::WhitelistedTokens, &whitelist);
+        });
+
+        let admin = Address::generate(&env);
+        let salt = BytesN::from_array(&env, &[7u8; 32]);
+        let predicted = client.predict_vault_address(&admin, &salt);
+        let deployer_address = env.as_contract(&factory_id, || {
+            env.deployer()
+                .with_current_contract(VestingFactory::vault_salt(&env, &admin, &salt))
+                .deployed_address()
+        });
+        let deployed =
+            client.deploy_vault_contract_with_salt(&admin, &1_000_000i128, &token, &false, &salt);
+
+        assert_eq!(predicted, deployed);
+        assert_eq!(deployer_address, deployed);
+        assert_eq!(client.get_deployed_contracts(), Vec::from_array(&env, [deployed]));
+    }
+}
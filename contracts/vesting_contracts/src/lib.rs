@@ -1,11 +1,45 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, token, vec, Address, Env, IntoVal, Map, Symbol, Vec, String};
+// The create_vault_* entry points take a full schedule; the argument helpers that
+// #[contractimpl] generates for them don't inherit per-function #[allow]s.
+#![allow(clippy::too_many_arguments)]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, token, vec, Address, Env, IntoVal, Map, String, Symbol,
-    Vec,
+    contract, contractimpl, contracttype, symbol_short, token, vec, xdr::ToXdr, Address, Bytes,
+    BytesN, Env, IntoVal, Map, String, Symbol, Vec,
 };
 
+// Contract identification, exposed via version() / contract_type()
+const CONTRACT_VERSION: (u32, u32, u32) = (1, 0, 0);
+const CONTRACT_TYPE: Symbol = symbol_short!("vesting");
+
+// Rounding mode for vesting math: true = round half up, false = truncate.
+// Rounding half up keeps every intermediate vested amount within half a unit
+// of the exact value instead of systematically stranding dust until end_time.
+const ROUND_HALF_UP: bool = true;
+
+// Upper bound on sample points for average_vested_over
+const MAX_AVERAGE_SAMPLES: u32 = 100;
+
+// Upper bound on vault ids scanned by a single paginated view call
+const MAX_PAGE_SIZE: u32 = 100;
+
+// Upper bound on milestones per vault; every claim iterates the full list
+pub const MAX_MILESTONES: u32 = 50;
+
+// Upper bound on the protocol fee taken from each claim (2%)
+pub const MAX_PROTOCOL_FEE_BPS: u32 = 200;
+
+// Longest vesting duration (end_time - start_time) clients should offer, 10 years.
+// Advisory only: reported by get_limits, not enforced on creation.
+pub const MAX_DURATION: u64 = 10 * 365 * 24 * 60 * 60;
+
+// Default claim inactivity before a recovery address may take over a vault (365 days)
+const DEFAULT_RECOVERY_INACTIVITY: u64 = 365 * 24 * 60 * 60;
+
+// Delegate permission bits stored in Vault::delegate_permissions
+pub const DELEGATE_CLAIM: u32 = 1 << 0;
+pub const DELEGATE_STAKE: u32 = 1 << 1;
+
 // DataKey for whitelisted tokens
 #[contracttype]
 pub enum WhitelistDataKey {
@@ -25,48 +59,81 @@ pub enum DataKey {
     UserVaults(Address),
     IsPaused,
     KeeperFees,
-    UserVaults(Address),
-    VaultMilestones(u64),
-    KeeperFees,
-    IsPaused,
     IsDeprecated,
     MigrationTarget,
     Token,       // yield-bearing token
     TotalShares, // remaining initial_deposit_shares
     TotalStaked,
+    CreationSealed,
+    BurnOnRevoke,
+    BurnAddress,
+    FreezeExpiry(u64),
+    MaxStakeBps,
+    ApprovedValidators,
+    IsYieldBearing,
+    AccruedYield,
+    KycRequired,
+    AllowedBeneficiaries,
+    RevokeDestination(u64),
+    MaxOutflowPerWindow,
+    OutflowWindowSeconds,
+    OutflowWindowStart,
+    OutflowInWindow,
+    VaultsImported,
+    TokenDecimals,
+    ClaimHook,
+    ClaimHookStrict,
+    CreationPaused,
+    OwnerClaimCooldown,
+    OwnerLastClaim,
+    MilestoneAmounts(u64),
+    AdminTransferDelay,
+    ProposalTime,
+    TransferAcceptanceRequired(u64),
+    PendingTransfer(u64),
+    MaxVaultsPerUser,
+    ClaimDeadline(u64),
+    TotalLocked,
+    TotalClaimed,
+    ProtocolFee,
+    MigrationExcluded,
+    TotalKeeperFees,
+    RecoveryAddress(u64),
+    LastActivity(u64),
+    RecoveryInactivityPeriod,
+    ValidatorStakes(u64),
+    ExternalId(BytesN<32>),
+    Treasury,
+    RunningTotalsBackfilled,
 }
 
 // Vault structure with lazy initialization
 #[contracttype]
 #[derive(Clone)]
 pub struct Vault {
-    pub total_amount: i128, // = initial_deposit_shares
-    pub released_amount: i128,
-    pub keeper_fee: i128,    // Fee paid to anyone who triggers auto_claim
-    pub staked_amount: i128, // Amount currently staked in external contract
-
     pub owner: Address,
     pub delegate: Option<Address>, // Optional delegate address for claiming
-    pub total_amount: i128,
+    pub delegate_permissions: u32, // Bitmask of DELEGATE_* rights granted to the delegate
+    pub max_claim_per_call: i128,  // Cap on a single claim (0 = unlimited)
+    pub category: Symbol,          // Reporting group, e.g. "team" (empty = uncategorized)
+    pub title: String,             // Short human-readable title (max 32 chars)
+    pub total_amount: i128,        // = initial_deposit_shares
     pub released_amount: i128,
     pub start_time: u64,
     pub end_time: u64,
-    pub keeper_fee: i128, // Fee paid to anyone who triggers auto_claim
-        pub title: String, // Short human-readable title (max 32 chars)
-    pub is_initialized: bool, // Lazy initialization flag
-    pub is_irrevocable: bool, // Security flag to prevent admin withdrawal
-    pub creation_time: u64, // Timestamp of creation for clawback grace period
-    pub is_transferable: bool, // Can the beneficiary transfer this vault?
-    pub step_duration: u64, // Duration of each vesting step in seconds (0 = linear)
+    pub keeper_fee: i128,    // Fee paid to anyone who triggers auto_claim
     pub staked_amount: i128, // Amount currently staked in external contract
-    pub is_frozen: bool, // Individual vault freeze flag for security investigations
-    pub creation_time: u64, // Timestamp of creation for clawback grace period
-    pub step_duration: u64, // Duration of each vesting step in seconds (0 = linear)
+    pub creation_time: u64,  // Timestamp of creation for clawback grace period
+    pub step_duration: u64,  // Duration of each vesting step in seconds (0 = linear)
+    pub step_offset: u64,    // Phase shift of step boundaries from start_time (< step_duration)
+    pub first_step_immediate: bool, // Step vaults: first step vests at start_time (upfront tranche)
+    pub cliff_unlock_bps: u32, // Share of total unlocked at start_time; the rest vests over the duration
 
     pub is_initialized: bool,  // Lazy initialization flag
     pub is_irrevocable: bool,  // Security flag to prevent admin withdrawal
     pub is_transferable: bool, // Can the beneficiary transfer this vault?
     pub is_frozen: bool,       // Individual vault freeze flag for security investigations
+    pub ever_frozen: bool,     // Set on the first freeze and never cleared
 }
 
 #[contracttype]
@@ -77,6 +144,26 @@ pub struct Milestone {
     pub is_unlocked: bool,
 }
 
+// A vault plus every per-vault storage entry, as moved by export_vault_state / import_vault_state
+#[contracttype]
+#[derive(Clone)]
+pub struct VaultRecord {
+    pub vault_id: u64,
+    pub vault: Vault,
+    pub milestones: Option<Vec<Milestone>>,
+    pub milestone_amounts: Option<Map<u64, i128>>,
+    pub claim_deadline: Option<u64>,
+    pub freeze_expiry: Option<u64>,
+    pub recovery_address: Option<Address>,
+    pub last_activity: Option<u64>,
+    pub validator_stakes: Option<Map<Address, i128>>,
+    pub revoke_destination: Option<Address>,
+    pub transfer_acceptance_required: Option<bool>,
+    // PendingTransfer (from, to), split because contracttype fields can't hold an optional tuple
+    pub pending_transfer_from: Option<Address>,
+    pub pending_transfer_to: Option<Address>,
+}
+
 #[contracttype]
 pub struct BatchCreateData {
     pub recipients: Vec<Address>,
@@ -106,8 +193,10 @@ pub struct VaultCreated {
     pub title: String,
 }
 
-// mod factory;
-// pub use factory::{VestingFactory, VestingFactoryClient};
+// The factory is its own contract; it is only compiled for its tests so its entry points
+// stay out of the vesting contract's wasm.
+#[cfg(test)]
+mod factory;
 
 #[contract]
 pub struct VestingContract;
@@ -140,7 +229,19 @@ impl VestingContract {
     }
 
     // Initialize contract with initial supply
-    pub fn initialize(env: Env, admin: Address, initial_supply: i128) {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        initial_supply: i128,
+        is_yield_bearing: bool,
+        kyc_required: bool,
+    ) {
+        if env.storage().instance().has(&DataKey::AdminAddress) {
+            panic!("Already initialized");
+        }
+        if initial_supply < 0 {
+            panic!("Initial supply must be non-negative");
+        }
         Self::require_not_deprecated(&env);
 
         env.storage().instance().set(&DataKey::AdminAddress, &admin);
@@ -151,16 +252,11 @@ impl VestingContract {
             .instance()
             .set(&DataKey::AdminBalance, &initial_supply);
 
-        env.storage().instance().set(&DataKey::AdminAddress, &admin);
-
         env.storage().instance().set(&DataKey::VaultCount, &0u64);
 
         // Initialize pause state to false (unpaused)
         env.storage().instance().set(&DataKey::IsPaused, &false);
 
-        // Initialize whitelisted tokens map
-        let whitelist: Map<Address, bool> = Map::new(&env);
-        env.storage()
         // Initialize deprecated state to false (active)
         env.storage().instance().set(&DataKey::IsDeprecated, &false);
 
@@ -175,6 +271,16 @@ impl VestingContract {
 
         env.storage().instance().set(&DataKey::TotalShares, &0i128);
         env.storage().instance().set(&DataKey::TotalStaked, &0i128);
+
+        // Rebasing tokens scale claims by the vault share of the balance; plain tokens pay 1:1
+        env.storage()
+            .instance()
+            .set(&DataKey::IsYieldBearing, &is_yield_bearing);
+
+        // When set, every vault recipient must be on the beneficiary allowlist
+        env.storage()
+            .instance()
+            .set(&DataKey::KycRequired, &kyc_required);
     }
 
     pub fn set_token(env: Env, token: Address) {
@@ -183,13 +289,48 @@ impl VestingContract {
             panic!("Token already set");
         }
         env.storage().instance().set(&DataKey::Token, &token);
+
+        // Cache decimals so UIs can render human amounts without querying the token
+        let decimals = token::Client::new(&env, &token).decimals();
+        env.storage()
+            .instance()
+            .set(&DataKey::TokenDecimals, &decimals);
+    }
+
+    pub fn get_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Token)
+    }
+
+    pub fn get_token_decimals(env: Env) -> Option<u32> {
+        env.storage().instance().get(&DataKey::TokenDecimals)
+    }
+
+    // Whole-token part of a raw amount, e.g. 12_345_0000000 at 7 decimals -> 12_345
+    pub fn to_display_amount(_env: Env, raw: i128, decimals: u32) -> i128 {
+        let scale = 10i128
+            .checked_pow(decimals)
+            .unwrap_or_else(|| panic!("Decimals too large"));
+        raw / scale
     }
 
-    fn get_token_client(env: &Env) -> token::Client {
+    fn get_token_client(env: &Env) -> token::Client<'_> {
         let token: Address = env
             .storage()
             .instance()
-            .set(&WhitelistDataKey::WhitelistedTokens, &whitelist);
+            .get(&DataKey::Token)
+            .unwrap_or_else(|| panic!("Token not set"));
+        token::Client::new(env, &token)
+    }
+
+    fn require_not_deprecated(env: &Env) {
+        if env
+            .storage()
+            .instance()
+            .get(&DataKey::IsDeprecated)
+            .unwrap_or(false)
+        {
+            panic!("Contract is deprecated");
+        }
     }
 
     // Helper function to check if caller is admin
@@ -202,6 +343,118 @@ impl VestingContract {
         admin.require_auth();
     }
 
+    fn require_creation_not_paused(env: &Env) {
+        if env
+            .storage()
+            .instance()
+            .get(&DataKey::CreationPaused)
+            .unwrap_or(false)
+        {
+            panic!("Vault creation is paused");
+        }
+    }
+
+    fn require_creation_not_sealed(env: &Env) {
+        if env
+            .storage()
+            .instance()
+            .get(&DataKey::CreationSealed)
+            .unwrap_or(false)
+        {
+            panic!("Vault creation is sealed");
+        }
+    }
+
+    fn require_beneficiary_allowed(env: &Env, beneficiary: &Address) {
+        let kyc_required: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::KycRequired)
+            .unwrap_or(false);
+        if !kyc_required {
+            return;
+        }
+        let allowed: Map<Address, bool> = env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowedBeneficiaries)
+            .unwrap_or(Map::new(env));
+        if !allowed.get(beneficiary.clone()).unwrap_or(false) {
+            panic!("Beneficiary not on allowlist");
+        }
+    }
+
+    // Panic if giving `owner` `adding` more vaults would exceed MaxVaultsPerUser (0 = unlimited)
+    fn require_user_vault_capacity(env: &Env, owner: &Address, adding: u32) {
+        let max_vaults: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxVaultsPerUser)
+            .unwrap_or(0);
+        if max_vaults == 0 {
+            return;
+        }
+        let user_vaults: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserVaults(owner.clone()))
+            .unwrap_or(Vec::new(env));
+        if user_vaults.len() + adding > max_vaults {
+            panic!("User vault limit reached");
+        }
+    }
+
+    fn require_before_claim_deadline(env: &Env, vault_id: u64) {
+        let deadline: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ClaimDeadline(vault_id))
+            .unwrap_or(0);
+        if deadline != 0 && env.ledger().timestamp() >= deadline {
+            panic!("Claim deadline passed");
+        }
+    }
+
+    fn require_within_claim_cap(vault: &Vault, claim_amount: i128) {
+        if vault.max_claim_per_call > 0 && claim_amount > vault.max_claim_per_call {
+            panic!("Exceeds per-call claim cap");
+        }
+    }
+
+    fn require_batch_lengths_match(batch_data: &BatchCreateData) {
+        let len = batch_data.recipients.len();
+        if batch_data.amounts.len() != len
+            || batch_data.start_times.len() != len
+            || batch_data.end_times.len() != len
+            || batch_data.keeper_fees.len() != len
+            || batch_data.step_durations.len() != len
+        {
+            panic!("Batch arrays length mismatch");
+        }
+    }
+
+    // Sum of batch amounts; a wrapped total could slip past the admin balance check
+    fn batch_total(batch_data: &BatchCreateData) -> i128 {
+        batch_data
+            .amounts
+            .iter()
+            .try_fold(0i128, |total, amount| total.checked_add(amount))
+            .unwrap_or_else(|| panic!("Batch total overflow"))
+    }
+
+    // Require the vault's delegate to authorize and to hold `permission`
+    fn require_delegate_permission(vault: &Vault, permission: u32) {
+        let delegate = vault
+            .delegate
+            .clone()
+            .unwrap_or_else(|| panic!("No delegate set for this vault"));
+        delegate.require_auth();
+
+        if vault.delegate_permissions & permission != permission {
+            panic!("Delegate lacks permission");
+        }
+    }
+
     fn require_milestones_configured(env: &Env, vault_id: u64) -> Vec<Milestone> {
         let milestones: Vec<Milestone> = env
             .storage()
@@ -228,8 +481,43 @@ impl VestingContract {
         }
     }
 
+    // Unlocked amount for a milestone vault: the sum of unlocked absolute amounts when the
+    // schedule was set with set_milestones_abs, otherwise the unlocked percentage of the total
+    fn milestone_unlocked_amount(env: &Env, vault_id: u64, total_amount: i128) -> i128 {
+        let milestones = Self::require_milestones_configured(env, vault_id);
+        if let Some(amounts) = env
+            .storage()
+            .instance()
+            .get::<DataKey, Map<u64, i128>>(&DataKey::MilestoneAmounts(vault_id))
+        {
+            let mut unlocked: i128 = 0;
+            for m in milestones.iter() {
+                if m.is_unlocked {
+                    unlocked += amounts.get(m.id).unwrap_or(0);
+                }
+            }
+            return unlocked.min(total_amount);
+        }
+        let unlocked_pct = Self::unlocked_percentage(&milestones);
+        Self::unlocked_amount(total_amount, unlocked_pct)
+    }
+
     fn unlocked_amount(total_amount: i128, unlocked_percentage: u32) -> i128 {
-        (total_amount * unlocked_percentage as i128) / 100i128
+        // Fully unlocked is exactly the total, whatever the rounding mode
+        if unlocked_percentage >= 100 {
+            return total_amount;
+        }
+        Self::mul_div_rounded(total_amount, unlocked_percentage as i128, 100i128)
+    }
+
+    // value * numerator / denominator using the crate-wide ROUND_HALF_UP mode
+    fn mul_div_rounded(value: i128, numerator: i128, denominator: i128) -> i128 {
+        let product = value * numerator;
+        if ROUND_HALF_UP {
+            (product + denominator / 2) / denominator
+        } else {
+            product / denominator
+        }
     }
 
     // Propose a new admin (first step of two-step process)
@@ -238,6 +526,24 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::ProposedAdmin, &new_admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::ProposalTime, &env.ledger().timestamp());
+    }
+
+    // Admin-only: seconds a proposal must wait before it can be accepted (0 = instant)
+    pub fn set_admin_transfer_delay(env: Env, delay_seconds: u64) {
+        Self::require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminTransferDelay, &delay_seconds);
+    }
+
+    pub fn get_admin_transfer_delay(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::AdminTransferDelay)
+            .unwrap_or(0)
     }
 
     // Accept admin ownership (second step of two-step process)
@@ -248,13 +554,66 @@ impl VestingContract {
             .get(&DataKey::ProposedAdmin)
             .unwrap_or_else(|| panic!("No proposed admin found"));
         proposed_admin.require_auth();
+
+        let proposal_time: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalTime)
+            .unwrap_or(0);
+        let delay = Self::get_admin_transfer_delay(env.clone());
+        if env.ledger().timestamp() < proposal_time + delay {
+            panic!("Admin transfer delay not elapsed");
+        }
+
         env.storage()
             .instance()
             .set(&DataKey::AdminAddress, &proposed_admin);
         env.storage().instance().remove(&DataKey::ProposedAdmin);
+        env.storage().instance().remove(&DataKey::ProposalTime);
+    }
+
+    // Withdraw a pending admin proposal before it is accepted (current admin only)
+    pub fn cancel_admin_proposal(env: Env) {
+        Self::require_admin(&env);
+        let proposed_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposedAdmin)
+            .unwrap_or_else(|| panic!("No proposed admin found"));
+        env.storage().instance().remove(&DataKey::ProposedAdmin);
+        env.storage().instance().remove(&DataKey::ProposalTime);
+
+        env.events().publish(
+            (Symbol::new(&env, "AdminProposalCancelled"),),
+            proposed_admin,
+        );
+    }
+
+    // Admin-only: keep a whitelisted token (e.g. a staking reward token) out of migrate_liquidity
+    pub fn set_migration_excluded(env: Env, token: Address, excluded: bool) {
+        Self::require_admin(&env);
+        let mut excluded_tokens: Map<Address, bool> = env
+            .storage()
+            .instance()
+            .get(&DataKey::MigrationExcluded)
+            .unwrap_or(Map::new(&env));
+        excluded_tokens.set(token, excluded);
+        env.storage()
+            .instance()
+            .set(&DataKey::MigrationExcluded, &excluded_tokens);
+    }
+
+    pub fn is_migration_excluded(env: Env, token: Address) -> bool {
+        let excluded_tokens: Map<Address, bool> = env
+            .storage()
+            .instance()
+            .get(&DataKey::MigrationExcluded)
+            .unwrap_or(Map::new(&env));
+        excluded_tokens.get(token).unwrap_or(false)
     }
 
     // Emergency migration: freeze contract and transfer all whitelisted token balances to V2.
+    // Excluded tokens stay behind and are reported as 0 in the returned map.
     // Admin-only. Sets `is_deprecated = true`.
     pub fn migrate_liquidity(env: Env, v2_contract_address: Address) -> Map<Address, i128> {
         Self::require_admin(&env);
@@ -263,11 +622,17 @@ impl VestingContract {
             panic!("v2_contract_address must differ from current contract");
         }
 
-        proposed_admin.require_auth();
+        let v2_type: Symbol = env.invoke_contract(
+            &v2_contract_address,
+            &Symbol::new(&env, "contract_type"),
+            Vec::new(&env),
+        );
+        if v2_type != CONTRACT_TYPE {
+            panic!("v2_contract_address is not a vesting contract");
+        }
 
         env.storage()
             .instance()
-            .set(&DataKey::AdminAddress, &proposed_admin);
             .set(&DataKey::MigrationTarget, &v2_contract_address);
 
         let whitelist: Map<Address, bool> = env
@@ -281,6 +646,10 @@ impl VestingContract {
             if !allowed {
                 continue;
             }
+            if Self::is_migration_excluded(env.clone(), token_address.clone()) {
+                migrated.set(token_address.clone(), 0);
+                continue;
+            }
             let token_client = token::Client::new(&env, &token_address);
             let balance: i128 = token_client.balance(&env.current_contract_address());
             if balance > 0 {
@@ -303,995 +672,2864 @@ impl VestingContract {
             v2_contract_address,
         );
 
-        env.storage().instance().remove(&DataKey::ProposedAdmin);
+        env.storage().instance().set(&DataKey::IsDeprecated, &true);
+        migrated
     }
 
-    // Get current admin address
-    pub fn get_admin(env: Env) -> Address {
-        env.storage()
+    // Export vaults [start, start + limit) so a successor contract can rebuild them with
+    // import_vault_state. Records are returned in id order and carry every per-vault key
+    // (milestones, deadlines, freezes, recovery, stakes, pending transfers).
+    // `limit` is capped at MAX_PAGE_SIZE.
+    pub fn export_vault_state(env: Env, start: u64, limit: u32) -> Vec<VaultRecord> {
+        let vault_count: u64 = env
+            .storage()
             .instance()
-            .get(&DataKey::AdminAddress)
-            .unwrap_or_else(|| panic!("Admin not set"))
-    }
-
-    // Get proposed admin address (if any)
-    pub fn get_proposed_admin(env: Env) -> Option<Address> {
-        env.storage().instance().get(&DataKey::ProposedAdmin)
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(vault_count + 1);
+
+        let mut records = Vec::new(&env);
+        for vault_id in start.max(1)..end {
+            let storage = env.storage().instance();
+            if let Some(vault) = storage.get::<DataKey, Vault>(&DataKey::VaultData(vault_id)) {
+                let pending: Option<(Address, Address)> =
+                    storage.get(&DataKey::PendingTransfer(vault_id));
+                records.push_back(VaultRecord {
+                    vault_id,
+                    vault,
+                    milestones: storage.get(&DataKey::VaultMilestones(vault_id)),
+                    milestone_amounts: storage.get(&DataKey::MilestoneAmounts(vault_id)),
+                    claim_deadline: storage.get(&DataKey::ClaimDeadline(vault_id)),
+                    freeze_expiry: storage.get(&DataKey::FreezeExpiry(vault_id)),
+                    recovery_address: storage.get(&DataKey::RecoveryAddress(vault_id)),
+                    last_activity: storage.get(&DataKey::LastActivity(vault_id)),
+                    validator_stakes: storage.get(&DataKey::ValidatorStakes(vault_id)),
+                    revoke_destination: storage.get(&DataKey::RevokeDestination(vault_id)),
+                    transfer_acceptance_required: storage
+                        .get(&DataKey::TransferAcceptanceRequired(vault_id)),
+                    pending_transfer_from: pending.clone().map(|(from, _)| from),
+                    pending_transfer_to: pending.map(|(_, to)| to),
+                });
+            }
+        }
+        records
     }
 
-    // Toggle pause state (Admin only) - "Big Red Button" for emergency pause
-    pub fn toggle_pause(env: Env) {
+    // Admin-only: rebuild exported vaults on a fresh contract under their original ids.
+    // Runs once, before any local vault exists. Unreleased amounts are funded from the admin
+    // balance, and TotalShares / TotalStaked are rebuilt from the imported vaults.
+    pub fn import_vault_state(env: Env, records: Vec<VaultRecord>) {
         Self::require_admin(&env);
 
-        let current_pause_state: bool = env
+        if env
             .storage()
             .instance()
-            .get(&DataKey::IsPaused)
-            .unwrap_or(false);
-
-        let new_pause_state = !current_pause_state;
-        env.storage()
-            .instance()
-            .set(&DataKey::IsPaused, &new_pause_state);
-
-        // Emit event for pause state change
-        env.events().publish(
-            (Symbol::new(&env, "PauseToggled"),),
-            (new_pause_state, env.ledger().timestamp()),
-        );
-    }
-
-    // Get current pause state
-    pub fn is_paused(env: Env) -> bool {
-        env.storage()
-            .instance()
-            .get(&DataKey::IsPaused)
+            .get(&DataKey::VaultsImported)
             .unwrap_or(false)
-    }
-
-    // Freeze a specific vault (Admin only) - prevents claims on this vault
-    pub fn freeze_vault(env: Env, vault_id: u64) {
-        Self::require_admin(&env);
-
-        let mut vault: Vault = env
+        {
+            panic!("Vaults already imported");
+        }
+        let vault_count: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
-
-        if vault.is_frozen {
-            panic!("Vault is already frozen");
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        if vault_count > 0 {
+            panic!("Cannot import into a contract with existing vaults");
         }
 
-        vault.is_frozen = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
-
-        env.events().publish(
-            (Symbol::new(&env, "VaultFrozen"), vault_id),
-            env.ledger().timestamp(),
-        );
-    }
-
-    // Unfreeze a specific vault (Admin only) - allows claims on this vault again
-    pub fn unfreeze_vault(env: Env, vault_id: u64) {
-        Self::require_admin(&env);
+        let mut unreleased: i128 = 0;
+        let mut staked: i128 = 0;
+        let mut max_vault_id: u64 = 0;
+        for record in records.iter() {
+            let vault_id = record.vault_id;
+            let vault = record.vault;
+            if vault_id == 0
+                || env
+                    .storage()
+                    .instance()
+                    .has(&DataKey::VaultData(vault_id))
+            {
+                panic!("Invalid or duplicate vault id");
+            }
+            max_vault_id = max_vault_id.max(vault_id);
+            unreleased += vault.total_amount - vault.released_amount;
+            staked += vault.staked_amount;
+
+            if vault.is_initialized {
+                let mut user_vaults: Vec<u64> = env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::UserVaults(vault.owner.clone()))
+                    .unwrap_or(Vec::new(&env));
+                user_vaults.push_back(vault_id);
+                env.storage()
+                    .instance()
+                    .set(&DataKey::UserVaults(vault.owner.clone()), &user_vaults);
+            }
+            Self::save_vault(&env, vault_id, &vault);
 
-        let mut vault: Vault = env
+            let storage = env.storage().instance();
+            if let Some(milestones) = record.milestones {
+                storage.set(&DataKey::VaultMilestones(vault_id), &milestones);
+            }
+            if let Some(amounts) = record.milestone_amounts {
+                storage.set(&DataKey::MilestoneAmounts(vault_id), &amounts);
+            }
+            if let Some(deadline) = record.claim_deadline {
+                storage.set(&DataKey::ClaimDeadline(vault_id), &deadline);
+            }
+            if let Some(expiry) = record.freeze_expiry {
+                storage.set(&DataKey::FreezeExpiry(vault_id), &expiry);
+            }
+            if let Some(recovery) = record.recovery_address {
+                storage.set(&DataKey::RecoveryAddress(vault_id), &recovery);
+            }
+            if let Some(last_activity) = record.last_activity {
+                storage.set(&DataKey::LastActivity(vault_id), &last_activity);
+            }
+            if let Some(stakes) = record.validator_stakes {
+                storage.set(&DataKey::ValidatorStakes(vault_id), &stakes);
+            }
+            if let Some(destination) = record.revoke_destination {
+                storage.set(&DataKey::RevokeDestination(vault_id), &destination);
+            }
+            if let Some(required) = record.transfer_acceptance_required {
+                storage.set(&DataKey::TransferAcceptanceRequired(vault_id), &required);
+            }
+            if let (Some(from), Some(to)) = (record.pending_transfer_from, record.pending_transfer_to) {
+                storage.set(&DataKey::PendingTransfer(vault_id), &(from, to));
+            }
+        }
+
+        let mut admin_balance: i128 = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
-
-        if !vault.is_frozen {
-            panic!("Vault is not frozen");
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        if admin_balance < unreleased {
+            panic!("Insufficient admin balance");
         }
+        admin_balance -= unreleased;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        let total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + unreleased));
+        let total_staked: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalStaked)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalStaked, &(total_staked + staked));
 
-        vault.is_frozen = false;
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .set(&DataKey::VaultCount, &max_vault_id);
+        env.storage().instance().set(&DataKey::VaultsImported, &true);
 
         env.events().publish(
-            (Symbol::new(&env, "VaultUnfrozen"), vault_id),
-            env.ledger().timestamp(),
+            (Symbol::new(&env, "VaultsImported"),),
+            (records.len(), unreleased),
         );
     }
 
-    // Check if a specific vault is frozen
-    pub fn is_vault_frozen(env: Env, vault_id: u64) -> bool {
-        let vault: Vault = env
-            .storage()
-            .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
+    // Semantic version of this contract as (major, minor, patch)
+    pub fn version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
 
-        vault.is_frozen
+    // Contract type tag ("vesting")
+    pub fn contract_type(_env: Env) -> Symbol {
+        CONTRACT_TYPE
     }
 
-    // Full initialization - writes all metadata immediately
-    pub fn create_vault_full(
-        env: Env,
-        owner: Address,
-        amount: i128,
-        start_time: u64,
-        end_time: u64,
-        keeper_fee: i128,
-        is_revocable: bool,
-        is_transferable: bool,
-        step_duration: u64,
-    ) -> u64 {
+    // Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::AdminAddress)
+            .unwrap_or_else(|| panic!("Admin not set"))
+    }
+
+    // Get proposed admin address (if any)
+    pub fn get_proposed_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::ProposedAdmin)
+    }
 
+    // Toggle pause state (Admin only) - "Big Red Button" for emergency pause
+    pub fn toggle_pause(env: Env) {
         Self::require_admin(&env);
 
-        let mut vault_count: u64 = env
+        let current_pause_state: bool = env
             .storage()
             .instance()
-            .get(&DataKey::VaultCount)
-            .unwrap_or(0);
-        vault_count += 1;
+            .get(&DataKey::IsPaused)
+            .unwrap_or(false);
 
-        let mut admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        if admin_balance < amount {
-            panic!("Insufficient admin balance");
-        }
-        admin_balance -= amount;
+        let new_pause_state = !current_pause_state;
         env.storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
-
-        let now = env.ledger().timestamp();
+            .set(&DataKey::IsPaused, &new_pause_state);
 
-        let vault = Vault {
-            title: String::from_slice(&env, ""),
-            owner: owner.clone(),
-            delegate: None,
-            total_amount: amount,
-            released_amount: 0,
-            start_time,
-            end_time,
-            keeper_fee,
-            title: String::from_slice(&env, ""),
-            is_initialized: true,
-            is_irrevocable: !is_revocable,
-            creation_time: now,
-            is_transferable,
-            step_duration,
-            staked_amount: 0,
-            is_frozen: false,
-        };
+        // Emit event for pause state change
+        env.events().publish(
+            (Symbol::new(&env, "PauseToggled"),),
+            (new_pause_state, env.ledger().timestamp()),
+        );
+    }
 
+    // Admin-only: cap how many vaults a single owner may hold (0 = unlimited)
+    pub fn set_max_vaults_per_user(env: Env, max_vaults_per_user: u32) {
+        Self::require_admin(&env);
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_count), &vault);
+            .set(&DataKey::MaxVaultsPerUser, &max_vaults_per_user);
+    }
 
-        let mut user_vaults: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserVaults(owner.clone()))
-            .unwrap_or(Vec::new(&env));
-        user_vaults.push_back(vault_count);
+    pub fn get_max_vaults_per_user(env: Env) -> u32 {
         env.storage()
             .instance()
-            .set(&DataKey::UserVaults(owner.clone()), &user_vaults);
+            .get(&DataKey::MaxVaultsPerUser)
+            .unwrap_or(0)
+    }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultCount, &vault_count);
+    // Creation limits for client-side validation:
+    // (MAX_DURATION, min_vault_amount, max_vaults_per_user).
+    // min_vault_amount is always 0: the contract has no minimum vault amount.
+    // max_vaults_per_user of 0 means unlimited.
+    pub fn get_limits(env: Env) -> (u64, i128, u32) {
+        (MAX_DURATION, 0, Self::get_max_vaults_per_user(env))
+    }
+
+    // Toggle creation pause (Admin only). Blocks vault creation without stopping claims.
+    pub fn toggle_creation_pause(env: Env) {
+        Self::require_admin(&env);
 
+        let paused = !Self::is_creation_paused(env.clone());
         env.storage()
             .instance()
-            .set(&DataKey::VaultCount, &vault_count);
+            .set(&DataKey::CreationPaused, &paused);
 
-        let mut total_shares: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalShares)
-            .unwrap_or(0);
-        total_shares += amount;
+        env.events().publish(
+            (Symbol::new(&env, "CreationPauseToggled"),),
+            (paused, env.ledger().timestamp()),
+        );
+    }
+
+    // Get current creation pause state
+    pub fn is_creation_paused(env: Env) -> bool {
         env.storage()
             .instance()
-            .set(&DataKey::TotalShares, &total_shares);
+            .get(&DataKey::CreationPaused)
+            .unwrap_or(false)
+    }
+
+    // Permanently block all vault creation (Admin only). Claims, revokes and views keep working.
+    pub fn seal_creation(env: Env) {
+        Self::require_admin(&env);
+        Self::require_creation_not_sealed(&env);
+
+        env.storage().instance().set(&DataKey::CreationSealed, &true);
 
-        let cliff_duration = start_time.saturating_sub(now);
-        let vault_created = VaultCreated {
-            vault_id: vault_count,
-            beneficiary: owner,
-            total_amount: amount,
-            cliff_duration,
-            start_time,
-            title: String::from_slice(&env, ""),
-        };
         env.events().publish(
-            (Symbol::new(&env, "VaultCreated"), vault_count),
-            vault_created,
+            (Symbol::new(&env, "CreationSealed"),),
+            env.ledger().timestamp(),
         );
+    }
 
-        vault_count
+    // Check whether vault creation has been sealed
+    pub fn is_creation_sealed(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::CreationSealed)
+            .unwrap_or(false)
     }
 
-    // Lazy initialization - writes minimal data initially
-    pub fn create_vault_lazy(
-        env: Env,
-        owner: Address,
-        amount: i128,
-        start_time: u64,
-        end_time: u64,
-        keeper_fee: i128,
-        is_revocable: bool,
-        is_transferable: bool,
-        step_duration: u64,
-    ) -> u64 {
+    // Admin-only: add a KYC-cleared beneficiary to the allowlist
+    pub fn add_allowed_beneficiary(env: Env, beneficiary: Address) {
         Self::require_admin(&env);
-
-        let mut vault_count: u64 = env
+        let mut allowed: Map<Address, bool> = env
             .storage()
             .instance()
-            .get(&DataKey::VaultCount)
-            .unwrap_or(0);
-        vault_count += 1;
+            .get(&DataKey::AllowedBeneficiaries)
+            .unwrap_or(Map::new(&env));
+        allowed.set(beneficiary, true);
+        env.storage()
+            .instance()
+            .set(&DataKey::AllowedBeneficiaries, &allowed);
+    }
 
-        let mut admin_balance: i128 = env
+    // Admin-only: remove a beneficiary from the allowlist. Existing vaults are unaffected.
+    pub fn remove_allowed_beneficiary(env: Env, beneficiary: Address) {
+        Self::require_admin(&env);
+        let mut allowed: Map<Address, bool> = env
             .storage()
             .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        if admin_balance < amount {
-            panic!("Insufficient admin balance");
-        }
-        admin_balance -= amount;
+            .get(&DataKey::AllowedBeneficiaries)
+            .unwrap_or(Map::new(&env));
+        allowed.remove(beneficiary);
         env.storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
-
-        let now = env.ledger().timestamp();
+            .set(&DataKey::AllowedBeneficiaries, &allowed);
+    }
 
-        let vault = Vault {
-            title: String::from_slice(&env, ""),
-            owner: owner.clone(),
-            delegate: None,
-            total_amount: amount,
-            released_amount: 0,
-            start_time,
-            end_time,
-            keeper_fee,
-            title: String::from_slice(&env, ""),
-            is_initialized: false, // Mark as lazy initialized
-            is_irrevocable: !is_revocable,
-            creation_time: now,
-            is_transferable,
-            step_duration,
-            staked_amount: 0,
-            is_frozen: false,
-        };
+    // Get current pause state
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::IsPaused)
+            .unwrap_or(false)
+    }
 
+    // Check whether claims are scaled for a rebasing (yield-bearing) token
+    pub fn is_yield_bearing(env: Env) -> bool {
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_count), &vault);
+            .get(&DataKey::IsYieldBearing)
+            .unwrap_or(true)
+    }
 
-        // Don't update user vaults list yet (lazy)
+    // Admin-only: cap the tokens claims may move out of the contract per window.
+    // A zero cap or window removes the limit.
+    pub fn set_outflow_limit(env: Env, max_outflow_per_window: i128, window_seconds: u64) {
+        Self::require_admin(&env);
+        if max_outflow_per_window < 0 {
+            panic!("Outflow limit must be non-negative");
+        }
         env.storage()
             .instance()
-            .set(&DataKey::VaultCount, &vault_count);
+            .set(&DataKey::MaxOutflowPerWindow, &max_outflow_per_window);
         env.storage()
             .instance()
-            .set(&DataKey::VaultCount, &vault_count);
-
-        let mut total_shares: i128 = env
-            .storage()
+            .set(&DataKey::OutflowWindowSeconds, &window_seconds);
+        env.storage()
             .instance()
-            .get(&DataKey::TotalShares)
-            .unwrap_or(0);
-        total_shares += amount;
+            .set(&DataKey::OutflowWindowStart, &env.ledger().timestamp());
+        env.storage().instance().set(&DataKey::OutflowInWindow, &0i128);
+    }
+
+    // Admin-only: contract notified via `on_claim(vault_id, amount, owner)` after every claim.
+    // Hook failures are ignored unless `strict` is set, in which case they revert the claim.
+    pub fn set_claim_hook(env: Env, hook: Option<Address>, strict: bool) {
+        Self::require_admin(&env);
+        match hook {
+            Some(hook) => env.storage().instance().set(&DataKey::ClaimHook, &hook),
+            None => env.storage().instance().remove(&DataKey::ClaimHook),
+        }
         env.storage()
             .instance()
-            .set(&DataKey::TotalShares, &total_shares);
+            .set(&DataKey::ClaimHookStrict, &strict);
+    }
 
-        let cliff_duration = start_time.saturating_sub(now);
-        let vault_created = VaultCreated {
-            vault_id: vault_count,
-            beneficiary: owner.clone(),
-            total_amount: amount,
-            cliff_duration,
-            start_time,
-            title: String::from_slice(&env, ""),
+    pub fn get_claim_hook(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::ClaimHook)
+    }
+
+    fn notify_claim_hook(env: &Env, vault_id: u64, amount: i128, owner: &Address) {
+        let hook: Address = match env.storage().instance().get(&DataKey::ClaimHook) {
+            Some(hook) => hook,
+            None => return,
         };
+        let strict: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::ClaimHookStrict)
+            .unwrap_or(false);
+
+        let func = Symbol::new(env, "on_claim");
+        let args = vec![
+            env,
+            vault_id.into_val(env),
+            amount.into_val(env),
+            owner.into_val(env),
+        ];
+        if strict {
+            env.invoke_contract::<()>(&hook, &func, args);
+        } else {
+            let _ = env.try_invoke_contract::<(), soroban_sdk::Error>(&hook, &func, args);
+        }
+    }
+
+    // Admin-only: minimum seconds between claims by the same owner across all their vaults.
+    // Zero disables the cooldown.
+    pub fn set_owner_claim_cooldown(env: Env, cooldown_seconds: u64) {
+        Self::require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::OwnerClaimCooldown, &cooldown_seconds);
+    }
+
+    pub fn get_owner_claim_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::OwnerClaimCooldown)
+            .unwrap_or(0)
+    }
+
+    // Admin-only: take `bps` basis points of every beneficiary payout and send it to `treasury`.
+    // Zero disables the fee.
+    pub fn set_protocol_fee(env: Env, bps: u32, treasury: Address) {
+        Self::require_admin(&env);
+        if bps > MAX_PROTOCOL_FEE_BPS {
+            panic!("Protocol fee exceeds maximum");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::ProtocolFee, &(bps, treasury.clone()));
+
         env.events().publish(
-            (Symbol::new(&env, "VaultCreated"), vault_count),
-            vault_created,
+            (Symbol::new(&env, "ProtocolFeeUpdated"),),
+            (bps, treasury),
         );
+    }
 
-        vault_count
+    // Current (bps, treasury), if a protocol fee was ever configured
+    pub fn get_protocol_fee(env: Env) -> Option<(u32, Address)> {
+        env.storage().instance().get(&DataKey::ProtocolFee)
     }
 
-    // Initialize vault metadata when needed (on-demand)
-    fn initialize_vault_metadata(env: &Env, vault_id: u64) -> bool {
-        if env
+    // Send the protocol fee on a payout to the treasury and return what is left for the owner
+    fn take_protocol_fee(env: &Env, token_client: &token::Client, vault_id: u64, amount: i128) -> i128 {
+        let (bps, treasury): (u32, Address) = match env.storage().instance().get(&DataKey::ProtocolFee) {
+            Some(fee) => fee,
+            None => return amount,
+        };
+        let fee = amount * bps as i128 / 10_000;
+        if fee <= 0 {
+            return amount;
+        }
+
+        token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+        env.events().publish(
+            (Symbol::new(env, "ProtocolFeePaid"), vault_id),
+            (treasury, fee),
+        );
+        amount - fee
+    }
+
+    // Stamp the vault's last activity, enforce the per-owner cooldown and stamp this claim
+    // as the owner's latest
+    fn record_owner_claim(env: &Env, vault_id: u64, owner: &Address) {
+        env.storage()
+            .instance()
+            .set(&DataKey::LastActivity(vault_id), &env.ledger().timestamp());
+
+        let cooldown: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::IsDeprecated)
-            .unwrap_or(false)
-        {
-            return false;
+            .get(&DataKey::OwnerClaimCooldown)
+            .unwrap_or(0);
+        if cooldown == 0 {
+            return;
         }
 
-        let vault: Vault = env
+        let now = env.ledger().timestamp();
+        let mut last_claims: Map<Address, u64> = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
+            .get(&DataKey::OwnerLastClaim)
+            .unwrap_or(Map::new(env));
+        if let Some(last) = last_claims.get(owner.clone()) {
+            if now < last + cooldown {
+                panic!("Owner cooldown active");
+            }
+        }
+        last_claims.set(owner.clone(), now);
+        env.storage()
+            .instance()
+            .set(&DataKey::OwnerLastClaim, &last_claims);
+    }
 
-        if !vault.is_initialized {
-            let mut updated_vault = vault.clone();
-            updated_vault.is_initialized = true;
+    // Persist a vault, folding its change in unreleased / released amounts into the running
+    // TotalLocked / TotalClaimed. Every VaultData write goes through here.
+    fn save_vault(env: &Env, vault_id: u64, vault: &Vault) {
+        let (old_locked, old_claimed) = match env
+            .storage()
+            .instance()
+            .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+        {
+            Some(old) => (old.total_amount - old.released_amount, old.released_amount),
+            None => (0, 0),
+        };
 
-            env.storage()
-                .instance()
-                .set(&DataKey::VaultData(vault_id), &updated_vault);
+        let total_locked: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalLocked)
+            .unwrap_or(0);
+        let total_claimed: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalClaimed)
+            .unwrap_or(0);
+        env.storage().instance().set(
+            &DataKey::TotalLocked,
+            &(total_locked + (vault.total_amount - vault.released_amount) - old_locked),
+        );
+        env.storage().instance().set(
+            &DataKey::TotalClaimed,
+            &(total_claimed + vault.released_amount - old_claimed),
+        );
 
-            let mut user_vaults: Vec<u64> = env
-                .storage()
-                .instance()
-                .get(&DataKey::UserVaults(updated_vault.owner.clone()))
-                .unwrap_or(Vec::new(env));
-            user_vaults.push_back(vault_id);
-            env.storage()
-                .instance()
-                .set(&DataKey::UserVaults(updated_vault.owner), &user_vaults);
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultData(vault_id), vault);
+    }
 
-            true
-        } else {
-            false // Already initialized
+    // Count `amount` against the current outflow window, starting a new window once the old one elapses
+    fn record_outflow(env: &Env, amount: i128) {
+        let max_outflow: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxOutflowPerWindow)
+            .unwrap_or(0);
+        let window_seconds: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::OutflowWindowSeconds)
+            .unwrap_or(0);
+        if max_outflow == 0 || window_seconds == 0 {
+            return;
         }
-    }
 
-    // Helper to calculate vested amount based on time (linear or step)
-    fn calculate_time_vested_amount(env: &Env, vault: &Vault) -> i128 {
         let now = env.ledger().timestamp();
-        if now < vault.start_time {
-        if now <= vault.start_time {
-            return 0;
-        }
-        if now >= vault.end_time {
-            return vault.total_amount;
+        let window_start: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::OutflowWindowStart)
+            .unwrap_or(now);
+        let mut used: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::OutflowInWindow)
+            .unwrap_or(0);
+        if now >= window_start + window_seconds {
+            env.storage().instance().set(&DataKey::OutflowWindowStart, &now);
+            used = 0;
         }
 
-        let duration = vault.end_time - vault.start_time;
-        if duration == 0 {
-            return vault.total_amount;
+        if used + amount > max_outflow {
+            panic!("Outflow limit reached");
         }
+        env.storage()
+            .instance()
+            .set(&DataKey::OutflowInWindow, &(used + amount));
+    }
 
-        let elapsed = now - vault.start_time;
-        let effective_elapsed = if vault.step_duration > 0 {
-            (elapsed / vault.step_duration) * vault.step_duration
-        } else {
-            elapsed
-        };
+    // Freeze a specific vault (Admin only) - prevents claims on this vault
+    pub fn freeze_vault(env: Env, vault_id: u64) {
+        Self::require_admin(&env);
 
-        if vault.step_duration > 0 {
-            // Periodic vesting: calculate vested = (elapsed / step_duration) * rate * step_duration
-            // Rate is total_amount / duration, so: vested = (elapsed / step_duration) * (total_amount / duration) * step_duration
-            // This simplifies to: vested = (elapsed / step_duration) * total_amount * step_duration / duration
-            let completed_steps = elapsed / vault.step_duration;
-            let rate_per_second = vault.total_amount / duration as i128;
-            let vested = completed_steps as i128 * rate_per_second * vault.step_duration as i128;
-            
-            // Ensure we don't exceed total amount
-            if vested > vault.total_amount {
-                vault.total_amount
-            } else {
-                vested
-            }
-        } else {
-            // Linear vesting
-            (vault.total_amount * elapsed as i128) / duration as i128
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if Self::is_freeze_active(&env, vault_id, &vault) {
+            panic!("Vault is already frozen");
         }
-        (vault.total_amount * effective_elapsed as i128) / duration as i128
+
+        vault.is_frozen = true;
+        vault.ever_frozen = true;
+        Self::save_vault(&env, vault_id, &vault);
+        env.storage()
+            .instance()
+            .remove(&DataKey::FreezeExpiry(vault_id));
+
+        env.events().publish(
+            (Symbol::new(&env, "VaultFrozen"), vault_id),
+            env.ledger().timestamp(),
+        );
     }
 
-    // Claim tokens from vault
-    pub fn claim_tokens(env: Env, vault_id: u64, claim_amount: i128) -> i128 {
-        // Check if contract is paused
-        if Self::is_paused(env.clone()) {
-            panic!("Contract is paused - all withdrawals are disabled");
+    // Owner-initiated freeze for a suspected key compromise. Only the admin can unfreeze,
+    // so whoever holds the compromised key cannot reverse it.
+    pub fn self_freeze_vault(env: Env, vault_id: u64) {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.owner.require_auth();
+
+        // A running timed freeze may be made indefinite; an indefinite one cannot be re-applied
+        if vault.is_frozen
+            && !env
+                .storage()
+                .instance()
+                .has(&DataKey::FreezeExpiry(vault_id))
+        {
+            panic!("Vault is already frozen");
         }
 
+        vault.is_frozen = true;
+        vault.ever_frozen = true;
+        Self::save_vault(&env, vault_id, &vault);
+        env.storage()
+            .instance()
+            .remove(&DataKey::FreezeExpiry(vault_id));
+
+        env.events().publish(
+            (Symbol::new(&env, "VaultSelfFrozen"), vault_id),
+            (vault.owner, env.ledger().timestamp()),
+        );
+    }
+
+    // Freeze a vault until `unfreeze_at` (Admin only) - the freeze lifts automatically at that time.
+    // `unfreeze_at == 0` means indefinite, same as freeze_vault.
+    pub fn freeze_vault_until(env: Env, vault_id: u64, unfreeze_at: u64) {
+        Self::require_admin(&env);
+
         let mut vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        // Check if vault is frozen
-        if vault.is_frozen {
-            panic!("Vault is frozen - claims are disabled");
+        if Self::is_freeze_active(&env, vault_id, &vault) {
+            panic!("Vault is already frozen");
         }
 
-        if !vault.is_initialized {
-            panic!("Vault not initialized");
-        }
-        if claim_amount <= 0 {
-            panic!("Claim amount must be positive");
+        let now = env.ledger().timestamp();
+        if unfreeze_at != 0 && unfreeze_at <= now {
+            panic!("Freeze expiry must be in the future");
         }
 
-        let unlocked_amount =
-            if env.storage().instance().has(&DataKey::VaultMilestones(vault_id)) {
-                let milestones = Self::require_milestones_configured(&env, vault_id);
-                let unlocked_pct = Self::unlocked_percentage(&milestones);
-                Self::unlocked_amount(vault.total_amount, unlocked_pct)
-            } else {
-                Self::calculate_time_vested_amount(&env, &vault)
-            };
+        vault.is_frozen = true;
+        vault.ever_frozen = true;
+        Self::save_vault(&env, vault_id, &vault);
+        env.storage()
+            .instance()
+            .set(&DataKey::FreezeExpiry(vault_id), &unfreeze_at);
 
-        vault.owner.require_auth();
+        env.events().publish(
+            (Symbol::new(&env, "VaultFrozenUntil"), vault_id),
+            (now, unfreeze_at),
+        );
+    }
 
-        let unlocked_amount = if env
+    // Get the automatic unfreeze time of a vault (0 = no expiry)
+    pub fn get_freeze_expiry(env: Env, vault_id: u64) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FreezeExpiry(vault_id))
+            .unwrap_or(0)
+    }
+
+    // A frozen vault stays frozen until unfrozen, or until its freeze expiry passes
+    fn is_freeze_active(env: &Env, vault_id: u64, vault: &Vault) -> bool {
+        if !vault.is_frozen {
+            return false;
+        }
+        let unfreeze_at = Self::get_freeze_expiry(env.clone(), vault_id);
+        unfreeze_at == 0 || env.ledger().timestamp() < unfreeze_at
+    }
+
+    // Unfreeze a specific vault (Admin only) - allows claims on this vault again
+    pub fn unfreeze_vault(env: Env, vault_id: u64) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
             .storage()
             .instance()
-            .has(&DataKey::VaultMilestones(vault_id))
-        {
-            let milestones = Self::require_milestones_configured(&env, vault_id);
-            let unlocked_pct = Self::unlocked_percentage(&milestones);
-            Self::unlocked_amount(vault.total_amount, unlocked_pct)
-        } else {
-            Self::calculate_time_vested_amount(&env, &vault)
-        };
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
 
-        let liquid_balance = vault.total_amount - vault.released_amount - vault.staked_amount;
-        if claim_amount > liquid_balance {
-            let deficit = claim_amount - liquid_balance;
+        if !vault.is_frozen {
+            panic!("Vault is not frozen");
+        }
 
-            let staking_contract: Address = env
+        vault.is_frozen = false;
+        Self::save_vault(&env, vault_id, &vault);
+        env.storage()
+            .instance()
+            .remove(&DataKey::FreezeExpiry(vault_id));
+
+        env.events().publish(
+            (Symbol::new(&env, "VaultUnfrozen"), vault_id),
+            env.ledger().timestamp(),
+        );
+    }
+
+    // Admin-only: freeze many vaults at once. Missing or already-frozen vaults are skipped.
+    pub fn batch_freeze(env: Env, vault_ids: Vec<u64>) -> u32 {
+        Self::require_admin(&env);
+
+        let mut frozen: u32 = 0;
+        for vault_id in vault_ids.iter() {
+            if let Some(mut vault) = env
                 .storage()
                 .instance()
-                .get(&Symbol::new(&env, "StakingContract"))
-                .expect("Staking contract not set");
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                if Self::is_freeze_active(&env, vault_id, &vault) {
+                    continue;
+                }
+                vault.is_frozen = true;
+                vault.ever_frozen = true;
+                Self::save_vault(&env, vault_id, &vault);
+                env.storage()
+                    .instance()
+                    .remove(&DataKey::FreezeExpiry(vault_id));
+                frozen += 1;
+            }
+        }
 
-            let args = vec![&env, vault_id.into_val(&env), deficit.into_val(&env)];
-            env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "unstake"), args);
+        env.events().publish(
+            (Symbol::new(&env, "BatchFrozen"),),
+            (frozen, env.ledger().timestamp()),
+        );
+        frozen
+    }
 
-            vault.staked_amount -= deficit;
+    // Admin-only: unfreeze many vaults at once. Missing or unfrozen vaults are skipped.
+    pub fn batch_unfreeze(env: Env, vault_ids: Vec<u64>) -> u32 {
+        Self::require_admin(&env);
 
-            let mut total_staked: i128 = env
+        let mut unfrozen: u32 = 0;
+        for vault_id in vault_ids.iter() {
+            if let Some(mut vault) = env
                 .storage()
                 .instance()
-                .get(&DataKey::TotalStaked)
-                .unwrap_or(0);
-            total_staked -= deficit;
-            env.storage()
-                .instance()
-                .set(&DataKey::TotalStaked, &total_staked);
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                if !vault.is_frozen {
+                    continue;
+                }
+                vault.is_frozen = false;
+                Self::save_vault(&env, vault_id, &vault);
+                env.storage()
+                    .instance()
+                    .remove(&DataKey::FreezeExpiry(vault_id));
+                unfrozen += 1;
+            }
         }
 
-        let available_to_claim = unlocked_amount - vault.released_amount;
-        if available_to_claim <= 0 {
-            panic!("No tokens available to claim");
-        }
-        if claim_amount > available_to_claim {
-            panic!("Insufficient unlocked tokens to claim");
-        }
+        env.events().publish(
+            (Symbol::new(&env, "BatchUnfrozen"),),
+            (unfrozen, env.ledger().timestamp()),
+        );
+        unfrozen
+    }
+
+    // Check if a specific vault is frozen
+    pub fn is_vault_frozen(env: Env, vault_id: u64) -> bool {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        Self::is_freeze_active(&env, vault_id, &vault)
+    }
+
+    // Check if a vault has ever been frozen, even if it is currently unfrozen
+    pub fn was_ever_frozen(env: Env, vault_id: u64) -> bool {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.ever_frozen
+    }
+
+    // List currently frozen vault ids in [start, start + limit). `limit` is capped at MAX_PAGE_SIZE.
+    pub fn get_frozen_vaults(env: Env, start: u64, limit: u32) -> Vec<u64> {
+        let vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(vault_count + 1);
+
+        let mut frozen = Vec::new(&env);
+        for vault_id in start.max(1)..end {
+            if let Some(vault) = env
+                .storage()
+                .instance()
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                if Self::is_freeze_active(&env, vault_id, &vault) {
+                    frozen.push_back(vault_id);
+                }
+            }
+        }
+        frozen
+    }
+
+    // Vault ids in [start, start + limit) that are fully vested by `before_ts` and still hold
+    // unreleased tokens, for forecasting full-unlock events. `limit` is capped at MAX_PAGE_SIZE.
+    pub fn vaults_vesting_before(env: Env, before_ts: u64, start: u64, limit: u32) -> Vec<u64> {
+        let vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(vault_count + 1);
+
+        let mut matching = Vec::new(&env);
+        for vault_id in start.max(1)..end {
+            if let Some(vault) = env
+                .storage()
+                .instance()
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                if vault.end_time <= before_ts && vault.released_amount < vault.total_amount {
+                    matching.push_back(vault_id);
+                }
+            }
+        }
+        matching
+    }
+
+    // Full initialization - writes all metadata immediately
+    pub fn create_vault_full(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
+        Self::require_admin(&env);
+        Self::internal_create_vault_full(
+            env,
+            owner,
+            amount,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_revocable,
+            is_transferable,
+            step_duration,
+        )
+    }
+
+    // create_vault_full without the admin auth check; callers must have authorized the admin
+    fn internal_create_vault_full(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
+        Self::require_creation_not_sealed(&env);
+        Self::require_creation_not_paused(&env);
+        Self::require_beneficiary_allowed(&env, &owner);
+        Self::require_user_vault_capacity(&env, &owner, 1);
+
+        let mut vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        vault_count += 1;
+
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        if admin_balance < amount {
+            panic!("Insufficient admin balance");
+        }
+        admin_balance -= amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        let now = env.ledger().timestamp();
+
+        let vault = Vault {
+            title: String::from_slice(&env, ""),
+            owner: owner.clone(),
+            delegate: None,
+            delegate_permissions: 0,
+            max_claim_per_call: 0,
+            category: symbol_short!(""),
+            first_step_immediate: false,
+            cliff_unlock_bps: 0,
+            total_amount: amount,
+            released_amount: 0,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_initialized: true,
+            is_irrevocable: !is_revocable,
+            creation_time: now,
+            is_transferable,
+            step_duration,
+            step_offset: 0,
+            staked_amount: 0,
+            is_frozen: false,
+            ever_frozen: false,
+        };
+
+        Self::save_vault(&env, vault_count, &vault);
+
+        let mut user_vaults: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserVaults(owner.clone()))
+            .unwrap_or(Vec::new(&env));
+        user_vaults.push_back(vault_count);
+        env.storage()
+            .instance()
+            .set(&DataKey::UserVaults(owner.clone()), &user_vaults);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &vault_count);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &vault_count);
+
+        let mut total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        total_shares += amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
+
+        let cliff_duration = start_time.saturating_sub(now);
+        let vault_created = VaultCreated {
+            vault_id: vault_count,
+            beneficiary: owner,
+            total_amount: amount,
+            cliff_duration,
+            start_time,
+            title: String::from_slice(&env, ""),
+        };
+        env.events().publish(
+            (Symbol::new(&env, "VaultCreated"), vault_count),
+            vault_created,
+        );
+
+        vault_count
+    }
+
+    // Same as create_vault_full, tagging the new vault with a reporting category
+    pub fn create_vault_with_category(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+        category: Symbol,
+    ) -> u64 {
+        let vault_id = Self::create_vault_full(
+            env.clone(),
+            owner,
+            amount,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_revocable,
+            is_transferable,
+            step_duration,
+        );
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap();
+        vault.category = category;
+        Self::save_vault(&env, vault_id, &vault);
+        vault_id
+    }
+
+    // Same as create_vault_full, keyed by a client-supplied `external_id` so retries are safe:
+    // an id that was already processed returns its vault id without creating another vault.
+    // A retry must repeat the original parameters; a reused id with different ones is rejected.
+    // The early return skips the creation seal/pause checks, since nothing is created.
+    pub fn create_vault_idempotent(
+        env: Env,
+        external_id: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
+        Self::require_admin(&env);
+
+        // Fingerprint of the creation parameters, checked against retries
+        let params: Bytes = (
+            owner.clone(),
+            amount,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_revocable,
+            is_transferable,
+            step_duration,
+        )
+            .to_xdr(&env);
+        let params_hash: BytesN<32> = env.crypto().sha256(&params).into();
+
+        let key = DataKey::ExternalId(external_id);
+        if let Some((vault_id, seen_hash)) =
+            env.storage().instance().get::<DataKey, (u64, BytesN<32>)>(&key)
+        {
+            if seen_hash != params_hash {
+                panic!("External id already used with different parameters");
+            }
+            return vault_id;
+        }
+
+        let vault_id = Self::internal_create_vault_full(
+            env.clone(),
+            owner,
+            amount,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_revocable,
+            is_transferable,
+            step_duration,
+        );
+        env.storage().instance().set(&key, &(vault_id, params_hash));
+        vault_id
+    }
+
+    // Same as create_vault_full, but first pulls exactly `amount` of the vesting token from the
+    // admin into the contract, so the new vault is backed 1:1 by deposited tokens
+    pub fn create_vault_funded(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminAddress)
+            .unwrap_or_else(|| panic!("Admin not set"));
+        admin.require_auth();
+
+        Self::get_token_client(&env).transfer(&admin, env.current_contract_address(), &amount);
+
+        // Deposited tokens enter the admin pool (and the supply) before being allocated
+        let admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &(admin_balance + amount));
+        let initial_supply: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::InitialSupply)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::InitialSupply, &(initial_supply + amount));
+
+        Self::internal_create_vault_full(
+            env,
+            owner,
+            amount,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_revocable,
+            is_transferable,
+            step_duration,
+        )
+    }
+
+    // Admin-only: tag a vault with a reporting category (empty symbol clears it)
+    pub fn set_vault_category(env: Env, vault_id: u64, category: Symbol) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        vault.category = category;
+        Self::save_vault(&env, vault_id, &vault);
+    }
+
+    // List vault ids in [start, start + limit) tagged with `category`. `limit` is capped at MAX_PAGE_SIZE.
+    pub fn get_vaults_by_category(env: Env, category: Symbol, start: u64, limit: u32) -> Vec<u64> {
+        let vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(vault_count + 1);
+
+        let mut matching = Vec::new(&env);
+        for vault_id in start.max(1)..end {
+            if let Some(vault) = env
+                .storage()
+                .instance()
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                if vault.category == category {
+                    matching.push_back(vault_id);
+                }
+            }
+        }
+        matching
+    }
+
+    // Lazy initialization - writes minimal data initially
+    pub fn create_vault_lazy(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
+        Self::require_admin(&env);
+        Self::require_creation_not_sealed(&env);
+        Self::require_creation_not_paused(&env);
+        Self::require_beneficiary_allowed(&env, &owner);
+        Self::require_user_vault_capacity(&env, &owner, 1);
+
+        let mut vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        vault_count += 1;
+
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        if admin_balance < amount {
+            panic!("Insufficient admin balance");
+        }
+        admin_balance -= amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        let now = env.ledger().timestamp();
+
+        let vault = Vault {
+            title: String::from_slice(&env, ""),
+            owner: owner.clone(),
+            delegate: None,
+            delegate_permissions: 0,
+            max_claim_per_call: 0,
+            category: symbol_short!(""),
+            first_step_immediate: false,
+            cliff_unlock_bps: 0,
+            total_amount: amount,
+            released_amount: 0,
+            start_time,
+            end_time,
+            keeper_fee,
+            is_initialized: false, // Mark as lazy initialized
+            is_irrevocable: !is_revocable,
+            creation_time: now,
+            is_transferable,
+            step_duration,
+            step_offset: 0,
+            staked_amount: 0,
+            is_frozen: false,
+            ever_frozen: false,
+        };
+
+        Self::save_vault(&env, vault_count, &vault);
+
+        // Don't update user vaults list yet (lazy)
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &vault_count);
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &vault_count);
+
+        let mut total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        total_shares += amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
+
+        let cliff_duration = start_time.saturating_sub(now);
+        let vault_created = VaultCreated {
+            vault_id: vault_count,
+            beneficiary: owner.clone(),
+            total_amount: amount,
+            cliff_duration,
+            start_time,
+            title: String::from_slice(&env, ""),
+        };
+        env.events().publish(
+            (Symbol::new(&env, "VaultCreated"), vault_count),
+            vault_created,
+        );
+
+        vault_count
+    }
+
+    // Initialize vault metadata when needed (on-demand)
+    fn initialize_vault_metadata(env: &Env, vault_id: u64) -> bool {
+        if env
+            .storage()
+            .instance()
+            .get(&DataKey::IsDeprecated)
+            .unwrap_or(false)
+        {
+            return false;
+        }
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            let mut updated_vault = vault.clone();
+            updated_vault.is_initialized = true;
+
+            Self::save_vault(env, vault_id, &updated_vault);
+
+            let mut user_vaults: Vec<u64> = env
+                .storage()
+                .instance()
+                .get(&DataKey::UserVaults(updated_vault.owner.clone()))
+                .unwrap_or(Vec::new(env));
+            user_vaults.push_back(vault_id);
+            env.storage()
+                .instance()
+                .set(&DataKey::UserVaults(updated_vault.owner), &user_vaults);
+
+            true
+        } else {
+            false // Already initialized
+        }
+    }
+
+    // Force lazy initialization of a vault so it shows up in its owner's UserVaults.
+    // Callable by anyone; returns false if the vault was already initialized.
+    pub fn reconcile_lazy_vault(env: Env, vault_id: u64) -> bool {
+        Self::initialize_vault_metadata(&env, vault_id)
+    }
+
+    // Admin-only: initialize every lazy vault in [start, start + limit). Returns how many were initialized.
+    pub fn sweep_uninitialized(env: Env, start: u64, limit: u32) -> u32 {
+        Self::require_admin(&env);
+
+        let vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        let end = start.saturating_add(limit as u64).min(vault_count + 1);
+
+        let mut initialized: u32 = 0;
+        for vault_id in start.max(1)..end {
+            if env
+                .storage()
+                .instance()
+                .has(&DataKey::VaultData(vault_id))
+                && Self::initialize_vault_metadata(&env, vault_id)
+            {
+                initialized += 1;
+            }
+        }
+
+        initialized
+    }
+
+    // Helper to calculate vested amount based on time (linear or step)
+    fn calculate_time_vested_amount(env: &Env, vault: &Vault) -> i128 {
+        Self::calculate_time_vested_amount_at(vault, env.ledger().timestamp())
+    }
+
+    // Same as calculate_time_vested_amount, evaluated at an arbitrary timestamp
+    fn calculate_time_vested_amount_at(vault: &Vault, now: u64) -> i128 {
+        let immediate_step = vault.first_step_immediate && vault.step_duration > 0;
+        let unlocks_at_start = immediate_step || vault.cliff_unlock_bps > 0;
+        if now < vault.start_time || (now == vault.start_time && !unlocks_at_start) {
+            return 0;
+        }
+        if now >= vault.end_time {
+            return vault.total_amount;
+        }
+
+        let duration = vault.end_time - vault.start_time;
+        if duration == 0 {
+            return vault.total_amount;
+        }
+
+        let elapsed = now - vault.start_time;
+        // Step boundaries are aligned to start_time + step_offset; linear vaults (step 0) use elapsed
+        let effective_elapsed = match elapsed
+            .saturating_sub(vault.step_offset)
+            .checked_div(vault.step_duration)
+        {
+            Some(steps) => ((steps + immediate_step as u64) * vault.step_duration).min(duration),
+            None => elapsed,
+        };
+
+        // The cliff slice is available from start_time; the remainder vests over the duration
+        let cliff_slice =
+            Self::mul_div_rounded(vault.total_amount, vault.cliff_unlock_bps as i128, 10_000);
+        cliff_slice
+            + Self::mul_div_rounded(
+                vault.total_amount - cliff_slice,
+                effective_elapsed as i128,
+                duration as i128,
+            )
+    }
+
+    // Time-weighted average vested amount over [from, to] for reporting.
+    // Samples the time-based vesting at `samples` evenly spaced points (both ends included).
+    pub fn average_vested_over(env: Env, vault_id: u64, from: u64, to: u64, samples: u32) -> i128 {
+        if samples == 0 || samples > MAX_AVERAGE_SAMPLES {
+            panic!("Invalid sample count");
+        }
+        if from > to {
+            panic!("Invalid time range");
+        }
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let span = to - from;
+        let mut sum: i128 = 0;
+        for i in 0..samples {
+            let at = if samples == 1 {
+                from
+            } else {
+                // In i128 so a wide range can't overflow; the offset never exceeds `span`
+                from + Self::mul_div_rounded(span as i128, i as i128, (samples - 1) as i128) as u64
+            };
+            sum += Self::calculate_time_vested_amount_at(&vault, at);
+        }
+
+        sum / samples as i128
+    }
+
+    // Claim tokens from vault. If staked tokens must be pulled back to cover the claim,
+    // validators in `unstake_order` are drained first (then any others the vault staked with);
+    // `None` unstakes in aggregate.
+    pub fn claim_tokens(
+        env: Env,
+        vault_id: u64,
+        claim_amount: i128,
+        unstake_order: Option<Vec<Address>>,
+    ) -> i128 {
+        Self::internal_claim(env, vault_id, Some(claim_amount), None, unstake_order, false)
+    }
+
+    // Claim everything currently unlocked and not yet released
+    pub fn claim_max(env: Env, vault_id: u64) -> i128 {
+        Self::internal_claim(env, vault_id, None, None, None, false)
+    }
+
+    // Owner-authorized claim submitted by a relayer, who keeps `relayer_fee` of the payout
+    pub fn claim_via_relayer(
+        env: Env,
+        vault_id: u64,
+        claim_amount: i128,
+        relayer: Address,
+        relayer_fee: i128,
+    ) -> i128 {
+        Self::internal_claim(env, vault_id, Some(claim_amount), Some((relayer, relayer_fee)), None, false)
+    }
+
+    // Shared claim path for claim_tokens / claim_max / claim_via_relayer / claim_as_delegate.
+    // `None` claims the full available amount; a relayer, if given, is paid its fee out of the
+    // transfer. With `as_delegate` the vault's delegate authorizes instead of the owner.
+    fn internal_claim(
+        env: Env,
+        vault_id: u64,
+        claim_amount: Option<i128>,
+        relayer: Option<(Address, i128)>,
+        unstake_order: Option<Vec<Address>>,
+        as_delegate: bool,
+    ) -> i128 {
+        // Check if contract is paused
+        if Self::is_paused(env.clone()) {
+            panic!("Contract is paused - all withdrawals are disabled");
+        }
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        // Check if vault is frozen (timed freezes lift automatically)
+        if Self::is_freeze_active(&env, vault_id, &vault) {
+            panic!("Vault is frozen - claims are disabled");
+        }
+        Self::require_before_claim_deadline(&env, vault_id);
+
+        // First interaction with a lazy vault surfaces it in the owner's UserVaults
+        if !vault.is_initialized {
+            if !Self::initialize_vault_metadata(&env, vault_id) {
+                panic!("Vault not initialized");
+            }
+            vault.is_initialized = true;
+        }
+        if claim_amount.is_some_and(|amount| amount <= 0) {
+            panic!("Claim amount must be positive");
+        }
+
+        if as_delegate {
+            Self::require_delegate_permission(&vault, DELEGATE_CLAIM);
+        } else {
+            vault.owner.require_auth();
+        }
+
+        let unlocked_amount = if env
+            .storage()
+            .instance()
+            .has(&DataKey::VaultMilestones(vault_id))
+        {
+            Self::milestone_unlocked_amount(&env, vault_id, vault.total_amount)
+        } else {
+            Self::calculate_time_vested_amount(&env, &vault)
+        };
+        let claim_amount = match claim_amount {
+            Some(amount) => {
+                Self::require_within_claim_cap(&vault, amount);
+                amount
+            }
+            // claim_max takes as much as the per-call cap allows
+            None if vault.max_claim_per_call > 0 => {
+                (unlocked_amount - vault.released_amount).min(vault.max_claim_per_call)
+            }
+            None => unlocked_amount - vault.released_amount,
+        };
+
+        let available_to_claim = unlocked_amount - vault.released_amount;
+        if available_to_claim <= 0 {
+            panic!("No tokens available to claim");
+        }
+        if claim_amount > available_to_claim {
+            panic!("Insufficient unlocked tokens to claim");
+        }
+
+        // Only a validated claim may pull tokens back from the staking contract
+        let liquid_balance = vault.total_amount - vault.released_amount - vault.staked_amount;
+        if claim_amount > liquid_balance {
+            let deficit = claim_amount - liquid_balance;
+
+            let staking_contract: Address = env
+                .storage()
+                .instance()
+                .get(&Symbol::new(&env, "StakingContract"))
+                .expect("Staking contract not set");
+
+            match unstake_order {
+                // Per-validator unstakes via the staking contract's
+                // `unstake_from(vault_id, amount, validator)`
+                Some(order) => {
+                    let drained = Self::drain_validator_stakes(&env, vault_id, deficit, &order);
+                    let mut remaining = deficit;
+                    for (validator, amount) in drained.iter() {
+                        let args = vec![
+                            &env,
+                            vault_id.into_val(&env),
+                            amount.into_val(&env),
+                            validator.into_val(&env),
+                        ];
+                        env.invoke_contract::<()>(
+                            &staking_contract,
+                            &Symbol::new(&env, "unstake_from"),
+                            args,
+                        );
+                        remaining -= amount;
+                    }
+                    // Stake not attributed to any validator is unstaked in aggregate
+                    if remaining > 0 {
+                        let args = vec![&env, vault_id.into_val(&env), remaining.into_val(&env)];
+                        env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "unstake"), args);
+                    }
+                }
+                None => {
+                    let args = vec![&env, vault_id.into_val(&env), deficit.into_val(&env)];
+                    env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "unstake"), args);
+                    Self::drain_validator_stakes(&env, vault_id, deficit, &Vec::new(&env));
+                }
+            }
+
+            Self::record_unstake(&env, vault_id, &mut vault, deficit);
+        }
+
+        // YIELD DISTRIBUTION - only vault-owned portion
+        let token_client = Self::get_token_client(&env);
+        let current_balance = token_client.balance(&env.current_contract_address());
+        let admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+
+        let total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        let total_staked: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalStaked)
+            .unwrap_or(0);
+        let liquid_shares = total_shares - total_staked;
+
+        let vault_portion = (current_balance - admin_balance).max(0);
+        let transfer_amount = if Self::is_yield_bearing(env.clone()) && liquid_shares > 0 {
+            (claim_amount * vault_portion) / liquid_shares
+        } else {
+            claim_amount
+        };
+
+        vault.released_amount += claim_amount;
+        let mut updated_total_shares = total_shares;
+        updated_total_shares -= claim_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &updated_total_shares);
+        Self::save_vault(&env, vault_id, &vault);
+
+        Self::record_outflow(&env, transfer_amount);
+        Self::record_owner_claim(&env, vault_id, &vault.owner);
+
+        let mut owner_amount = Self::take_protocol_fee(&env, &token_client, vault_id, transfer_amount);
+        if let Some((relayer, relayer_fee)) = relayer {
+            if relayer_fee < 0 || relayer_fee >= owner_amount {
+                panic!("Relayer fee must be less than the payout");
+            }
+            token_client.transfer(&env.current_contract_address(), &relayer, &relayer_fee);
+            owner_amount -= relayer_fee;
+
+            env.events().publish(
+                (Symbol::new(&env, "RelayedClaim"), vault_id),
+                (relayer, relayer_fee, owner_amount),
+            );
+        }
+
+        token_client.transfer(
+            &env.current_contract_address(),
+            &vault.owner,
+            &owner_amount,
+        );
+        Self::notify_claim_hook(&env, vault_id, owner_amount, &vault.owner);
+
+        claim_amount
+    }
+
+    /// Transfers the beneficiary role of a vault to a new address.
+    /// Only the admin can perform this action (e.g., in case of lost keys).
+    pub fn transfer_beneficiary(env: Env, vault_id: u64, new_address: Address) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        Self::require_valid_new_owner(&env, &vault, &new_address);
+
+        let old_owner = vault.owner.clone();
+
+        if vault.is_initialized {
+            let old_vaults: Vec<u64> = env
+                .storage()
+                .instance()
+                .get(&DataKey::UserVaults(old_owner.clone()))
+                .unwrap_or(Vec::new(&env));
+
+            let mut updated_old_vaults = Vec::new(&env);
+            for id in old_vaults.iter() {
+                if id != vault_id {
+                    updated_old_vaults.push_back(id);
+                }
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::UserVaults(old_owner.clone()), &updated_old_vaults);
+
+            let mut new_vaults: Vec<u64> = env
+                .storage()
+                .instance()
+                .get(&DataKey::UserVaults(new_address.clone()))
+                .unwrap_or(Vec::new(&env));
+            new_vaults.push_back(vault_id);
+            env.storage()
+                .instance()
+                .set(&DataKey::UserVaults(new_address.clone()), &new_vaults);
+        }
+
+        vault.owner = new_address.clone();
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "BeneficiaryUpdated"), vault_id),
+            (old_owner.clone(), new_address),
+        );
+    }
+
+    // Set delegate address for a vault (only owner can call)
+    pub fn set_delegate(
+        env: Env,
+        vault_id: u64,
+        delegate: Option<Address>,
+        delegate_permissions: u32,
+    ) {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        vault.owner.require_auth();
+        if delegate.as_ref() == Some(&env.current_contract_address()) {
+            panic!("Delegate cannot be the contract address");
+        }
+
+        let old_delegate = vault.delegate.clone();
+
+        vault.delegate_permissions = if delegate.is_some() {
+            delegate_permissions
+        } else {
+            0
+        };
+        vault.delegate = delegate.clone();
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "DelegateUpdated"), vault_id),
+            (old_delegate, delegate),
+        );
+    }
+
+    // Security response for a compromised delegate key: the owner or the admin drops the
+    // delegate and its permissions and freezes the vault until the admin unfreezes it.
+    pub fn emergency_clear_delegate(env: Env, vault_id: u64, caller: Address) {
+        caller.require_auth();
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if caller != vault.owner && caller != Self::get_admin(env.clone()) {
+            panic!("Only owner or admin can clear the delegate");
+        }
+
+        let old_delegate = vault
+            .delegate
+            .clone()
+            .unwrap_or_else(|| panic!("No delegate set for this vault"));
+
+        // Frozen with no expiry, even if a timed freeze was already running
+        vault.is_frozen = true;
+        vault.ever_frozen = true;
+        env.storage()
+            .instance()
+            .remove(&DataKey::FreezeExpiry(vault_id));
+        vault.delegate = None;
+        vault.delegate_permissions = 0;
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "DelegateRevoked"), vault_id),
+            (old_delegate, caller, env.ledger().timestamp()),
+        );
+    }
+
+    // Set delegates on many vaults in one call.
+    // Auth model: by default every listed vault's owner must authorize the call
+    // (an org signing for several owners needs each owner's signature). With
+    // `admin_override` set, only the admin authorizes and owner auth is skipped.
+    // Delegates assigned here receive claim rights only (DELEGATE_CLAIM).
+    pub fn batch_set_delegates(env: Env, assignments: Map<u64, Address>, admin_override: bool) {
+        if admin_override {
+            Self::require_admin(&env);
+        }
+
+        for (vault_id, delegate) in assignments.iter() {
+            let mut vault: Vault = env
+                .storage()
+                .instance()
+                .get(&DataKey::VaultData(vault_id))
+                .unwrap_or_else(|| panic!("Vault not found"));
+
+            if !vault.is_initialized {
+                panic!("Vault not initialized");
+            }
+
+            if !admin_override {
+                vault.owner.require_auth();
+            }
+
+            let old_delegate = vault.delegate.clone();
+
+            vault.delegate = Some(delegate.clone());
+            vault.delegate_permissions = DELEGATE_CLAIM;
+            Self::save_vault(&env, vault_id, &vault);
+
+            env.events().publish(
+                (Symbol::new(&env, "DelegateUpdated"), vault_id),
+                (old_delegate, Some(delegate)),
+            );
+        }
+    }
+
+    // Current owner (beneficiary) of a vault
+    pub fn get_owner(env: Env, vault_id: u64) -> Address {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        vault.owner
+    }
+
+    // Ownership proof for cross-contract integrations
+    pub fn is_owner(env: Env, vault_id: u64, who: Address) -> bool {
+        Self::get_owner(env, vault_id) == who
+    }
+
+    // Current delegate of a vault, if any
+    pub fn get_delegate(env: Env, vault_id: u64) -> Option<Address> {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        vault.delegate
+    }
+
+    // Permission bits (DELEGATE_CLAIM, DELEGATE_STAKE) granted to the vault's delegate
+    pub fn get_delegate_permissions(env: Env, vault_id: u64) -> u32 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        vault.delegate_permissions
+    }
+
+    // Claim tokens as delegate (tokens still go to owner)
+    pub fn claim_as_delegate(env: Env, vault_id: u64, claim_amount: i128) -> i128 {
+        Self::internal_claim(env, vault_id, Some(claim_amount), None, None, true)
+    }
+
+    pub fn set_milestones(env: Env, vault_id: u64, milestones: Vec<Milestone>) {
+        Self::require_admin(&env);
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        // Releases made under time vesting don't map onto the milestone schedule
+        if vault.released_amount > 0 {
+            panic!("Cannot set milestones after tokens were released");
+        }
+
+        if milestones.is_empty() {
+            panic!("No milestones provided");
+        }
+        if milestones.len() > MAX_MILESTONES {
+            panic!("Too many milestones");
+        }
+
+        let mut total_pct: u32 = 0;
+        let mut seen: Map<u64, bool> = Map::new(&env);
+        for m in milestones.iter() {
+            if m.percentage == 0 {
+                panic!("Milestone percentage must be positive");
+            }
+            if m.percentage > 100 {
+                panic!("Milestone percentage too large");
+            }
+            if seen.contains_key(m.id) {
+                panic!("Duplicate milestone id");
+            }
+            seen.set(m.id, true);
+            total_pct = total_pct.saturating_add(m.percentage);
+        }
+        if total_pct > 100 {
+            panic!("Total milestone percentage exceeds 100");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultMilestones(vault_id), &milestones);
+        env.storage()
+            .instance()
+            .remove(&DataKey::MilestoneAmounts(vault_id));
+        env.events().publish(
+            (Symbol::new(&env, "MilestonesSet"), vault_id),
+            (milestones.len(), total_pct),
+        );
+    }
+
+    // Admin-only: milestone schedule in absolute token amounts as (milestone id, amount).
+    // Amounts must sum to at most total_amount; unlocking releases exactly those amounts.
+    pub fn set_milestones_abs(env: Env, vault_id: u64, milestones: Vec<(u64, i128)>) {
+        Self::require_admin(&env);
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        // Releases made under time vesting don't map onto the milestone schedule
+        if vault.released_amount > 0 {
+            panic!("Cannot set milestones after tokens were released");
+        }
+
+        if milestones.is_empty() {
+            panic!("No milestones provided");
+        }
+        if milestones.len() > MAX_MILESTONES {
+            panic!("Too many milestones");
+        }
+
+        let mut total: i128 = 0;
+        let mut amounts: Map<u64, i128> = Map::new(&env);
+        let mut schedule: Vec<Milestone> = Vec::new(&env);
+        for (id, amount) in milestones.iter() {
+            if amount <= 0 {
+                panic!("Milestone amount must be positive");
+            }
+            if amount > vault.total_amount {
+                panic!("Milestone amount exceeds vault total");
+            }
+            if amounts.contains_key(id) {
+                panic!("Duplicate milestone id");
+            }
+            amounts.set(id, amount);
+            total = total
+                .checked_add(amount)
+                .unwrap_or_else(|| panic!("Milestone total overflow"));
+            schedule.push_back(Milestone {
+                id,
+                percentage: 0,
+                is_unlocked: false,
+            });
+        }
+        if total > vault.total_amount {
+            panic!("Total milestone amount exceeds vault total");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultMilestones(vault_id), &schedule);
+        env.storage()
+            .instance()
+            .set(&DataKey::MilestoneAmounts(vault_id), &amounts);
+        env.events().publish(
+            (Symbol::new(&env, "MilestonesSet"), vault_id),
+            (milestones.len(), total),
+        );
+    }
+
+    pub fn get_milestones(env: Env, vault_id: u64) -> Vec<Milestone> {
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultMilestones(vault_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Whether the vault vests by milestones (a non-empty milestone list) rather than by time
+    pub fn is_milestone_vault(env: Env, vault_id: u64) -> bool {
+        !Self::get_milestones(env, vault_id).is_empty()
+    }
+
+    pub fn unlock_milestone(env: Env, vault_id: u64, milestone_id: u64) {
+        Self::require_admin(&env);
+
+        let _vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let milestones = Self::require_milestones_configured(&env, vault_id);
+
+        let mut found = false;
+        let mut updated = Vec::new(&env);
+        for m in milestones.iter() {
+            if m.id == milestone_id {
+                found = true;
+                if m.is_unlocked {
+                    panic!("Milestone already unlocked");
+                }
+                updated.push_back(Milestone {
+                    id: m.id,
+                    percentage: m.percentage,
+                    is_unlocked: true,
+                });
+            } else {
+                updated.push_back(m);
+            }
+        }
+        if !found {
+            panic!("Milestone not found");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultMilestones(vault_id), &updated);
+        let timestamp = env.ledger().timestamp();
+        env.events().publish(
+            (Symbol::new(&env, "MilestoneUnlocked"), vault_id),
+            (milestone_id, timestamp),
+        );
+    }
+
+    // Admin-only: cap how much a single claim can withdraw from a vault (0 = unlimited)
+    pub fn set_claim_cap(env: Env, vault_id: u64, max_claim_per_call: i128) {
+        Self::require_admin(&env);
+
+        if max_claim_per_call < 0 {
+            panic!("Claim cap must be non-negative");
+        }
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.max_claim_per_call = max_claim_per_call;
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "ClaimCapUpdated"), vault_id),
+            max_claim_per_call,
+        );
+    }
+
+    // Admin-only: shift step boundaries to start_time + step_offset (before vesting starts)
+    pub fn set_step_offset(env: Env, vault_id: u64, step_offset: u64) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if vault.step_duration == 0 {
+            panic!("Vault does not use step vesting");
+        }
+        if step_offset >= vault.step_duration {
+            panic!("Step offset must be less than step duration");
+        }
+        if env.ledger().timestamp() >= vault.start_time {
+            panic!("Vesting has already started");
+        }
+
+        vault.step_offset = step_offset;
+        Self::save_vault(&env, vault_id, &vault);
+    }
+
+    // Admin-only: push a vault's end_time out and add `extra_amount` from the admin balance in
+    // one step. Vesting is recomputed over the new total and duration.
+    pub fn extend_and_increase(env: Env, vault_id: u64, new_end_time: u64, extra_amount: i128) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        if new_end_time < vault.end_time {
+            panic!("New end time must not be earlier than current end time");
+        }
+        if extra_amount < 0 {
+            panic!("Extra amount must be non-negative");
+        }
+
+        let admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        if admin_balance < extra_amount {
+            panic!("Insufficient admin balance");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &(admin_balance - extra_amount));
+
+        let total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + extra_amount));
+
+        let old_end_time = vault.end_time;
+        vault.end_time = new_end_time;
+        vault.total_amount += extra_amount;
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "VaultExtended"), vault_id),
+            (old_end_time, new_end_time, extra_amount),
+        );
+    }
+
+    // Admin-only: correct a vault's step_duration (0 = linear) before vesting starts.
+    // A step offset that no longer fits the new step is reset to zero.
+    pub fn set_step_duration(env: Env, vault_id: u64, step_duration: u64) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if env.ledger().timestamp() >= vault.start_time {
+            panic!("Vesting has already started");
+        }
+        if vault.released_amount > 0 {
+            panic!("Tokens already released");
+        }
+
+        let old_step_duration = vault.step_duration;
+        vault.step_duration = step_duration;
+        if vault.step_offset >= step_duration {
+            vault.step_offset = 0;
+        }
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "StepDurationUpdated"), vault_id),
+            (old_step_duration, step_duration),
+        );
+    }
+
+    // Admin-only: make a step vault's first step vest at start_time instead of one
+    // step_duration later. Like set_step_duration, only before vesting starts.
+    pub fn set_first_step_immediate(env: Env, vault_id: u64, immediate: bool) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if env.ledger().timestamp() >= vault.start_time {
+            panic!("Vesting has already started");
+        }
+
+        vault.first_step_immediate = immediate;
+        Self::save_vault(&env, vault_id, &vault);
+    }
+
+    // Admin-only: unlock `cliff_unlock_bps` of the total at start_time, with the remainder
+    // vesting over the schedule. Only before vesting starts.
+    pub fn set_cliff_unlock_bps(env: Env, vault_id: u64, cliff_unlock_bps: u32) {
+        Self::require_admin(&env);
+        if cliff_unlock_bps > 10_000 {
+            panic!("Cliff unlock exceeds 100%");
+        }
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if env.ledger().timestamp() >= vault.start_time {
+            panic!("Vesting has already started");
+        }
+
+        vault.cliff_unlock_bps = cliff_unlock_bps;
+        Self::save_vault(&env, vault_id, &vault);
+    }
+
+    // Admin-only: set a short title for a vault (max 32 bytes)
+    pub fn set_vault_title(env: Env, vault_id: u64, title: String) {
+        Self::require_admin(&env);
+
+        // Enforce max length (32 bytes)
+        if title.len() > 32 {
+            panic!("Title too long");
+        }
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.title = title;
+        Self::save_vault(&env, vault_id, &vault);
+    }
+
+    // Batch create vaults with lazy initialization
+    pub fn batch_create_vaults_lazy(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
+        Self::require_admin(&env);
+        Self::require_creation_not_sealed(&env);
+        Self::require_creation_not_paused(&env);
+        Self::require_batch_lengths_match(&batch_data);
+        let mut pending: Map<Address, u32> = Map::new(&env);
+        for recipient in batch_data.recipients.iter() {
+            Self::require_beneficiary_allowed(&env, &recipient);
+            let adding = pending.get(recipient.clone()).unwrap_or(0) + 1;
+            Self::require_user_vault_capacity(&env, &recipient, adding);
+            pending.set(recipient, adding);
+        }
+
+        let mut vault_ids = Vec::new(&env);
+        let initial_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+
+        let total_amount = Self::batch_total(&batch_data);
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        if admin_balance < total_amount {
+            panic!("Insufficient admin balance for batch");
+        }
+        admin_balance -= total_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        let now = env.ledger().timestamp();
+        for i in 0..batch_data.recipients.len() {
+            let vault_id = initial_count + i as u64 + 1;
+
+            let vault = Vault {
+                title: String::from_slice(&env, ""),
+                owner: batch_data.recipients.get(i).unwrap(),
+                delegate: None,
+                delegate_permissions: 0,
+                max_claim_per_call: 0,
+                category: symbol_short!(""),
+                first_step_immediate: false,
+                cliff_unlock_bps: 0,
+                total_amount: batch_data.amounts.get(i).unwrap(),
+                released_amount: 0,
+                start_time: batch_data.start_times.get(i).unwrap(),
+                end_time: batch_data.end_times.get(i).unwrap(),
+                keeper_fee: batch_data.keeper_fees.get(i).unwrap(),
+                is_initialized: false, // Lazy initialization
+                is_irrevocable: false, // Default to revocable for batch operations
+                creation_time: now,
+                is_transferable: false,
+                step_duration: batch_data.step_durations.get(i).unwrap_or(0),
+                step_offset: 0,
+                staked_amount: 0,
+                is_frozen: false,
+                ever_frozen: false,
+            };
+
+            Self::save_vault(&env, vault_id, &vault);
+            vault_ids.push_back(vault_id);
+
+            let start_time = batch_data.start_times.get(i).unwrap();
+            let cliff_duration = start_time.saturating_sub(now);
+            let vault_created = VaultCreated {
+                vault_id,
+                beneficiary: vault.owner.clone(),
+                total_amount: vault.total_amount,
+                cliff_duration,
+                start_time,
+                title: String::from_slice(&env, ""),
+            };
+            env.events()
+                .publish((Symbol::new(&env, "VaultCreated"), vault_id), vault_created);
+        }
+
+        let mut total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        total_shares += total_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
+
+        let final_count = initial_count + batch_data.recipients.len() as u64;
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &final_count);
+
+        vault_ids
+    }
+
+    // Batch create vaults with full initialization
+    pub fn batch_create_vaults_full(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
+        Self::require_admin(&env);
+        Self::require_creation_not_sealed(&env);
+        Self::require_creation_not_paused(&env);
+        Self::require_batch_lengths_match(&batch_data);
+        let mut pending: Map<Address, u32> = Map::new(&env);
+        for recipient in batch_data.recipients.iter() {
+            Self::require_beneficiary_allowed(&env, &recipient);
+            let adding = pending.get(recipient.clone()).unwrap_or(0) + 1;
+            Self::require_user_vault_capacity(&env, &recipient, adding);
+            pending.set(recipient, adding);
+        }
+
+        let mut vault_ids = Vec::new(&env);
+        let initial_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+
+        let total_amount = Self::batch_total(&batch_data);
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        if admin_balance < total_amount {
+            panic!("Insufficient admin balance for batch");
+        }
+        admin_balance -= total_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        let now = env.ledger().timestamp();
+        for i in 0..batch_data.recipients.len() {
+            let vault_id = initial_count + i as u64 + 1;
+
+            let vault = Vault {
+                title: String::from_slice(&env, ""),
+                owner: batch_data.recipients.get(i).unwrap(),
+                delegate: None,
+                delegate_permissions: 0,
+                max_claim_per_call: 0,
+                category: symbol_short!(""),
+                first_step_immediate: false,
+                cliff_unlock_bps: 0,
+                total_amount: batch_data.amounts.get(i).unwrap(),
+                released_amount: 0,
+                start_time: batch_data.start_times.get(i).unwrap(),
+                end_time: batch_data.end_times.get(i).unwrap(),
+                keeper_fee: batch_data.keeper_fees.get(i).unwrap(),
+                is_initialized: true,
+                is_irrevocable: false,
+                creation_time: now,
+                is_transferable: false,
+                step_duration: batch_data.step_durations.get(i).unwrap_or(0),
+                step_offset: 0,
+                staked_amount: 0,
+                is_frozen: false,
+                ever_frozen: false,
+            };
+
+            Self::save_vault(&env, vault_id, &vault);
+
+            let mut user_vaults: Vec<u64> = env
+                .storage()
+                .instance()
+                .get(&DataKey::UserVaults(vault.owner.clone()))
+                .unwrap_or(Vec::new(&env));
+            user_vaults.push_back(vault_id);
+            env.storage()
+                .instance()
+                .set(&DataKey::UserVaults(vault.owner.clone()), &user_vaults);
 
-        vault.released_amount += claim_amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            vault_ids.push_back(vault_id);
 
-        // YIELD DISTRIBUTION - only vault-owned portion
-        let token_client = Self::get_token_client(&env);
-        let current_balance = token_client.balance(&env.current_contract_address());
-        let admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
+            let start_time = batch_data.start_times.get(i).unwrap();
+            let cliff_duration = start_time.saturating_sub(now);
+            let vault_created = VaultCreated {
+                vault_id,
+                beneficiary: vault.owner.clone(),
+                total_amount: vault.total_amount,
+                cliff_duration,
+                start_time,
+                title: String::from_slice(&env, ""),
+            };
+            env.events()
+                .publish((Symbol::new(&env, "VaultCreated"), vault_id), vault_created);
+        }
 
-        let total_shares: i128 = env
+        let mut total_shares: i128 = env
             .storage()
             .instance()
             .get(&DataKey::TotalShares)
             .unwrap_or(0);
-        let total_staked: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalStaked)
-            .unwrap_or(0);
-        let liquid_shares = total_shares - total_staked;
-
-        let vault_portion = (current_balance - admin_balance).max(0);
-        let transfer_amount = if liquid_shares > 0 {
-            (claim_amount * vault_portion) / liquid_shares
-        } else {
-            claim_amount
-        };
-
-        vault.released_amount += claim_amount;
-        let mut updated_total_shares = total_shares;
-        updated_total_shares -= claim_amount;
+        total_shares += total_amount;
         env.storage()
             .instance()
-            .set(&DataKey::TotalShares, &updated_total_shares);
+            .set(&DataKey::TotalShares, &total_shares);
+
+        let final_count = initial_count + batch_data.recipients.len() as u64;
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
-
-        token_client.transfer(
-            &env.current_contract_address(),
-            &vault.owner,
-            &transfer_amount,
-        );
+            .set(&DataKey::VaultCount, &final_count);
 
-        claim_amount
+        vault_ids
     }
 
-    /// Transfers the beneficiary role of a vault to a new address.
-    /// Only the admin can perform this action (e.g., in case of lost keys).
-    pub fn transfer_beneficiary(env: Env, vault_id: u64, new_address: Address) {
+    // Admin-only: consolidate several vaults of one beneficiary on an identical schedule into a new vault.
+    // The originals are marked fully released and the merged totals move to the new vault.
+    pub fn merge_vaults(env: Env, vault_ids: Vec<u64>) -> u64 {
         Self::require_admin(&env);
 
-        let mut vault: Vault = env
+        if vault_ids.len() < 2 {
+            panic!("At least two vaults required to merge");
+        }
+
+        let first: Vault = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
+            .get(&DataKey::VaultData(vault_ids.get(0).unwrap()))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        let old_owner = vault.owner.clone();
+        let mut total_amount: i128 = 0;
+        let mut released_amount: i128 = 0;
+        let mut is_irrevocable = false;
+        let mut is_transferable = true;
+        let mut ever_frozen = false;
+        let mut seen: Map<u64, bool> = Map::new(&env);
 
-        if vault.is_initialized {
-            let old_vaults: Vec<u64> = env
+        for vault_id in vault_ids.iter() {
+            if seen.contains_key(vault_id) {
+                panic!("Duplicate vault id");
+            }
+            seen.set(vault_id, true);
+
+            let mut vault: Vault = env
                 .storage()
                 .instance()
-                .get(&DataKey::UserVaults(old_owner.clone()))
-                .unwrap_or(Vec::new(&env));
+                .get(&DataKey::VaultData(vault_id))
+                .unwrap_or_else(|| panic!("Vault not found"));
 
-            let mut updated_old_vaults = Vec::new(&env);
-            for id in old_vaults.iter() {
-                if id != vault_id {
-                    updated_old_vaults.push_back(id);
-                }
+            if !vault.is_initialized {
+                panic!("Vault not initialized");
             }
-            env.storage()
-                .instance()
-                .set(&DataKey::UserVaults(old_owner.clone()), &updated_old_vaults);
-
-            let mut new_vaults: Vec<u64> = env
+            if vault.owner != first.owner
+                || vault.start_time != first.start_time
+                || vault.end_time != first.end_time
+                || vault.step_duration != first.step_duration
+                || vault.step_offset != first.step_offset
+                || vault.first_step_immediate != first.first_step_immediate
+                || vault.cliff_unlock_bps != first.cliff_unlock_bps
+            {
+                panic!("Vaults are not compatible for merging");
+            }
+            if vault.is_frozen {
+                panic!("Cannot merge frozen vault");
+            }
+            if vault.staked_amount > 0 {
+                panic!("Cannot merge staked vault");
+            }
+            if env
                 .storage()
                 .instance()
-                .get(&DataKey::UserVaults(new_address.clone()))
-                .unwrap_or(Vec::new(&env));
-            new_vaults.push_back(vault_id);
-            env.storage()
-                .instance()
-                .set(&DataKey::UserVaults(new_address.clone()), &new_vaults);
+                .has(&DataKey::VaultMilestones(vault_id))
+            {
+                panic!("Cannot merge milestone vault");
+            }
+
+            total_amount += vault.total_amount;
+            released_amount += vault.released_amount;
+            is_irrevocable = is_irrevocable || vault.is_irrevocable;
+            is_transferable = is_transferable && vault.is_transferable;
+            ever_frozen = ever_frozen || vault.ever_frozen;
+
+            vault.released_amount = vault.total_amount;
+            Self::save_vault(&env, vault_id, &vault);
         }
 
-        vault.owner = new_address.clone();
-        env.storage()
+        let mut vault_count: u64 = env
+            .storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        vault_count += 1;
 
-        env.events().publish(
-            (Symbol::new(&env, "BeneficiaryUpdated"), vault_id),
-            (old_owner.clone(), new_address),
-        );
-    }
+        let merged = Vault {
+            title: String::from_slice(&env, ""),
+            owner: first.owner.clone(),
+            delegate: None,
+            delegate_permissions: 0,
+            max_claim_per_call: 0,
+            category: first.category.clone(),
+            first_step_immediate: first.first_step_immediate,
+            cliff_unlock_bps: first.cliff_unlock_bps,
+            total_amount,
+            released_amount,
+            start_time: first.start_time,
+            end_time: first.end_time,
+            keeper_fee: first.keeper_fee,
+            is_initialized: true,
+            is_irrevocable,
+            creation_time: env.ledger().timestamp(),
+            is_transferable,
+            step_duration: first.step_duration,
+            step_offset: first.step_offset,
+            staked_amount: 0,
+            is_frozen: false,
+            ever_frozen,
+        };
+        Self::save_vault(&env, vault_count, &merged);
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &vault_count);
 
-    // Set delegate address for a vault (only owner can call)
-    pub fn set_delegate(env: Env, vault_id: u64, delegate: Option<Address>) {
-        let mut vault: Vault = env
+        let user_vaults: Vec<u64> = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
-
-        if !vault.is_initialized {
-            panic!("Vault not initialized");
+            .get(&DataKey::UserVaults(first.owner.clone()))
+            .unwrap_or(Vec::new(&env));
+        let mut updated_user_vaults = Vec::new(&env);
+        for id in user_vaults.iter() {
+            if !seen.contains_key(id) {
+                updated_user_vaults.push_back(id);
+            }
         }
-
-        vault.owner.require_auth();
-
-        let old_delegate = vault.delegate.clone();
-
-        vault.delegate = delegate.clone();
+        updated_user_vaults.push_back(vault_count);
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .set(&DataKey::UserVaults(first.owner.clone()), &updated_user_vaults);
 
         env.events().publish(
-            (Symbol::new(&env, "DelegateUpdated"), vault_id),
-            (old_delegate, delegate),
+            (Symbol::new(&env, "VaultsMerged"), vault_count),
+            (vault_ids, total_amount, released_amount),
         );
-    }
 
-    // Claim tokens as delegate (tokens still go to owner)
-    pub fn claim_as_delegate(env: Env, vault_id: u64, claim_amount: i128) -> i128 {
-        // Check if contract is paused
-        if Self::is_paused(env.clone()) {
-            panic!("Contract is paused - all withdrawals are disabled");
-        }
+        vault_count
+    }
 
-        let vault: Vault = env
+    // Owner-only: carve part of a transferable vault into a new vault for another wallet.
+    // Total and released amounts are split proportionally so both vaults keep the same schedule.
+    pub fn split_vault(env: Env, vault_id: u64, split_amount: i128, new_owner: Address) -> u64 {
+        let mut vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        // Check if vault is frozen
-        if vault.is_frozen {
-            panic!("Vault is frozen - claims are disabled");
-        }
-
         if !vault.is_initialized {
             panic!("Vault not initialized");
         }
-        if claim_amount <= 0 {
-            panic!("Claim amount must be positive");
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
         }
 
-        let delegate = vault
-            .delegate
-            .clone()
-            .unwrap_or_else(|| panic!("No delegate set for this vault"));
-        delegate.require_auth();
+        vault.owner.require_auth();
 
-        let unlocked_amount = if env
+        if split_amount <= 0 || split_amount >= vault.total_amount {
+            panic!("Invalid split amount");
+        }
+        Self::require_valid_new_owner(&env, &vault, &new_owner);
+        Self::require_beneficiary_allowed(&env, &new_owner);
+        Self::require_user_vault_capacity(&env, &new_owner, 1);
+        if Self::is_freeze_active(&env, vault_id, &vault) {
+            panic!("Cannot split frozen vault");
+        }
+        Self::require_before_claim_deadline(&env, vault_id);
+        if vault.staked_amount > 0 {
+            panic!("Cannot split staked vault");
+        }
+        if env
             .storage()
             .instance()
             .has(&DataKey::VaultMilestones(vault_id))
         {
-            let milestones = Self::require_milestones_configured(&env, vault_id);
-            let unlocked_pct = Self::unlocked_percentage(&milestones);
-            Self::unlocked_amount(vault.total_amount, unlocked_pct)
-        } else {
-            Self::calculate_time_vested_amount(&env, &vault)
-        };
-        let available_to_claim = unlocked_amount - vault.released_amount;
-        if available_to_claim <= 0 {
-            panic!("No tokens available to claim");
-        }
-        if claim_amount > available_to_claim {
-            panic!("Insufficient unlocked tokens to claim");
+            panic!("Cannot split milestone vault");
         }
 
-        // YIELD DISTRIBUTION - only vault-owned portion
-        let token_client = Self::get_token_client(&env);
-        let current_balance = token_client.balance(&env.current_contract_address());
-        let admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
+        let split_released = vault.released_amount * split_amount / vault.total_amount;
+        vault.total_amount -= split_amount;
+        vault.released_amount -= split_released;
+        Self::save_vault(&env, vault_id, &vault);
 
-        let total_shares: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalShares)
-            .unwrap_or(0);
-        let total_staked: i128 = env
+        let mut vault_count: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::TotalStaked)
+            .get(&DataKey::VaultCount)
             .unwrap_or(0);
-        let liquid_shares = total_shares - total_staked;
+        vault_count += 1;
 
-        let vault_portion = (current_balance - admin_balance).max(0);
-        let transfer_amount = if liquid_shares > 0 {
-            (claim_amount * vault_portion) / liquid_shares
-        } else {
-            claim_amount
+        let split = Vault {
+            title: String::from_slice(&env, ""),
+            owner: new_owner.clone(),
+            delegate: None,
+            delegate_permissions: 0,
+            max_claim_per_call: vault.max_claim_per_call,
+            category: vault.category.clone(),
+            first_step_immediate: vault.first_step_immediate,
+            cliff_unlock_bps: vault.cliff_unlock_bps,
+            total_amount: split_amount,
+            released_amount: split_released,
+            start_time: vault.start_time,
+            end_time: vault.end_time,
+            keeper_fee: vault.keeper_fee,
+            is_initialized: true,
+            is_irrevocable: vault.is_irrevocable,
+            creation_time: env.ledger().timestamp(),
+            is_transferable: true,
+            step_duration: vault.step_duration,
+            step_offset: vault.step_offset,
+            staked_amount: 0,
+            is_frozen: false,
+            ever_frozen: false,
         };
-
-        let mut updated_vault = vault.clone();
-        updated_vault.released_amount += claim_amount;
+        Self::save_vault(&env, vault_count, &split);
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &updated_vault);
+            .set(&DataKey::VaultCount, &vault_count);
+        // The split-off tokens stay subject to the parent's claim deadline
+        if let Some(deadline) = env
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::ClaimDeadline(vault_id))
+        {
+            env.storage()
+                .instance()
+                .set(&DataKey::ClaimDeadline(vault_count), &deadline);
+        }
 
-        claim_amount
-        let mut updated_total_shares = total_shares;
-        updated_total_shares -= claim_amount;
-        env.storage()
+        let mut new_user_vaults: Vec<u64> = env
+            .storage()
             .instance()
-            .set(&DataKey::TotalShares, &updated_total_shares);
+            .get(&DataKey::UserVaults(new_owner.clone()))
+            .unwrap_or(Vec::new(&env));
+        new_user_vaults.push_back(vault_count);
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &updated_vault);
+            .set(&DataKey::UserVaults(new_owner.clone()), &new_user_vaults);
 
-        token_client.transfer(
-            &env.current_contract_address(),
-            &updated_vault.owner,
-            &transfer_amount,
+        env.events().publish(
+            (Symbol::new(&env, "VaultSplit"), vault_id),
+            (vault_count, new_owner, split_amount, split_released),
         );
 
-        transfer_amount
+        vault_count
     }
 
-    pub fn set_milestones(env: Env, vault_id: u64, milestones: Vec<Milestone>) {
-        Self::require_admin(&env);
-
+    // Get vault info (initializes if needed)
+    pub fn get_vault(env: Env, vault_id: u64) -> Vault {
         let vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
-        if !vault.is_initialized {
-            panic!("Vault not initialized");
-        }
-
-        if milestones.is_empty() {
-            panic!("No milestones provided");
-        }
-
-        let mut total_pct: u32 = 0;
-        let mut seen: Map<u64, bool> = Map::new(&env);
-        for m in milestones.iter() {
-            if m.percentage == 0 {
-                panic!("Milestone percentage must be positive");
-            }
-            if m.percentage > 100 {
-                panic!("Milestone percentage too large");
-            }
-            if seen.contains_key(m.id) {
-                panic!("Duplicate milestone id");
-            }
-            seen.set(m.id, true);
-            total_pct = total_pct.saturating_add(m.percentage);
-        }
-        if total_pct > 100 {
-            panic!("Total milestone percentage exceeds 100");
-        }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultMilestones(vault_id), &milestones);
-        env.events().publish(
-            (Symbol::new(&env, "MilestonesSet"), vault_id),
-            (milestones.len(), total_pct),
-        );
+        if !vault.is_initialized {
+            Self::initialize_vault_metadata(&env, vault_id);
+            env.storage()
+                .instance()
+                .get(&DataKey::VaultData(vault_id))
+                .unwrap_or_else(|| panic!("Vault not found"))
+        } else {
+            vault
+        }
     }
 
-    pub fn get_milestones(env: Env, vault_id: u64) -> Vec<Milestone> {
+    // Get user vaults as stored, without initializing lazy vaults (no storage writes)
+    pub fn get_user_vaults_view(env: Env, user: Address) -> Vec<u64> {
         env.storage()
             .instance()
-            .get(&DataKey::VaultMilestones(vault_id))
+            .get(&DataKey::UserVaults(user))
             .unwrap_or(Vec::new(&env))
     }
 
-    pub fn unlock_milestone(env: Env, vault_id: u64, milestone_id: u64) {
-        Self::require_admin(&env);
+    // Governance snapshot: for each user, the vested-but-unreleased amount (milestone-aware)
+    // summed across every vault they own as of now, lazy vaults included. Read-only; walks all
+    // vaults once, for at most MAX_PAGE_SIZE users per call.
+    pub fn snapshot_holdings(env: Env, users: Vec<Address>) -> Vec<i128> {
+        if users.len() > MAX_PAGE_SIZE {
+            panic!("Too many users");
+        }
 
-        let _vault: Vault = env
+        let mut held: Map<Address, i128> = Map::new(&env);
+        for user in users.iter() {
+            held.set(user, 0);
+        }
+
+        let vault_count: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
-
-        let milestones = Self::require_milestones_configured(&env, vault_id);
-
-        let mut found = false;
-        let mut updated = Vec::new(&env);
-        for m in milestones.iter() {
-            if m.id == milestone_id {
-                found = true;
-                if m.is_unlocked {
-                    panic!("Milestone already unlocked");
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        for vault_id in 1..=vault_count {
+            if let Some(vault) = env
+                .storage()
+                .instance()
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                if let Some(amount) = held.get(vault.owner.clone()) {
+                    let vested = Self::vested_amount(&env, vault_id, &vault);
+                    held.set(
+                        vault.owner.clone(),
+                        amount + (vested - vault.released_amount).max(0),
+                    );
                 }
-                updated.push_back(Milestone {
-                    id: m.id,
-                    percentage: m.percentage,
-                    is_unlocked: true,
-                });
-            } else {
-                updated.push_back(m);
             }
         }
-        if !found {
-            panic!("Milestone not found");
+
+        let mut holdings = Vec::new(&env);
+        for user in users.iter() {
+            holdings.push_back(held.get(user).unwrap_or(0));
         }
+        holdings
+    }
 
-        env.storage()
+    // Get user vaults (initializes all if needed)
+    pub fn get_user_vaults(env: Env, user: Address) -> Vec<u64> {
+        let vault_ids: Vec<u64> = env
+            .storage()
             .instance()
-            .set(&DataKey::VaultMilestones(vault_id), &updated);
-        let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (Symbol::new(&env, "MilestoneUnlocked"), vault_id),
-            (milestone_id, timestamp),
-        );
-    }
+            .get(&DataKey::UserVaults(user.clone()))
+            .unwrap_or(Vec::new(&env));
 
-    // Admin-only: set a short title for a vault (max 32 bytes)
-    pub fn set_vault_title(env: Env, vault_id: u64, title: String) {
-        Self::require_admin(&env);
+        for vault_id in vault_ids.iter() {
+            let vault: Vault = env
+                .storage()
+                .instance()
+                .get(&DataKey::VaultData(vault_id))
+                .unwrap_or_else(|| panic!("Vault not found"));
 
-        // Enforce max length (32 bytes)
-        if title.len() > 32 {
-            panic!("Title too long");
+            if !vault.is_initialized {
+                Self::initialize_vault_metadata(&env, vault_id);
+            }
         }
 
+        vault_ids
+    }
+
+    // Internal helper: revoke full unreleased amount from a vault and emit event.
+    // Does NOT update admin balance — caller is responsible for a single aggregated transfer.
+    fn internal_revoke_full(env: &Env, vault_id: u64) -> i128 {
         let mut vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        vault.title = title;
-        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        if vault.is_irrevocable {
+            panic!("Vault is irrevocable");
+        }
+        Self::require_not_staked(&vault);
+
+        let unreleased_amount = vault.total_amount - vault.released_amount;
+        if unreleased_amount <= 0 {
+            panic!("No tokens available to revoke");
+        }
+
+        vault.released_amount = vault.total_amount;
+        Self::save_vault(env, vault_id, &vault);
+
+        let timestamp = env.ledger().timestamp();
+        env.events().publish(
+            (Symbol::new(env, "TokensRevoked"), vault_id),
+            (unreleased_amount, timestamp),
+        );
+
+        unreleased_amount
     }
 
-    // Batch create vaults with lazy initialization
-    pub fn batch_create_vaults_lazy(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
+    // Admin-only: Revoke tokens from a vault and return them to admin
+    pub fn revoke_tokens(env: Env, vault_id: u64) -> i128 {
         Self::require_admin(&env);
 
-        let mut vault_ids = Vec::new(&env);
-        let initial_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::VaultCount)
-            .unwrap_or(0);
+        let returned = Self::internal_revoke_full(&env, vault_id);
 
-        let mut vault_ids = Vec::new(&env);
-        let initial_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::VaultCount)
-            .unwrap_or(0);
+        Self::settle_revoked(&env, returned);
 
-        let total_amount: i128 = batch_data.amounts.iter().sum();
-        let mut admin_balance: i128 = env
+        let mut total_shares: i128 = env
             .storage()
             .instance()
-            .get(&DataKey::AdminBalance)
+            .get(&DataKey::TotalShares)
             .unwrap_or(0);
-        if admin_balance < total_amount {
-            panic!("Insufficient admin balance for batch");
-        }
-        admin_balance -= total_amount;
+        total_shares -= returned;
         env.storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+            .set(&DataKey::TotalShares, &total_shares);
 
-        let now = env.ledger().timestamp();
-        for i in 0..batch_data.recipients.len() {
-            let vault_id = initial_count + i as u64 + 1;
+        returned
+    }
 
-            let vault = Vault {
-                title: String::from_slice(&env, ""),
-                owner: batch_data.recipients.get(i).unwrap(),
-                delegate: None,
-                total_amount: batch_data.amounts.get(i).unwrap(),
-                released_amount: 0,
-                start_time: batch_data.start_times.get(i).unwrap(),
-                end_time: batch_data.end_times.get(i).unwrap(),
-                keeper_fee: batch_data.keeper_fees.get(i).unwrap(),
-                title: String::from_slice(&env, ""),
-                is_initialized: false, // Lazy initialization
-                is_irrevocable: false, // Default to revocable for batch operations
-                is_initialized: false,
-                is_irrevocable: false,
-                creation_time: now,
-                is_transferable: false,
-                step_duration: batch_data.step_durations.get(i).unwrap_or(0),
-                staked_amount: 0,
-                is_frozen: false,
-            };
+    // Admin-only: revoke a vault and send the reclaimed tokens to `destination`
+    // (e.g. a recovery wallet) instead of crediting the admin balance
+    pub fn revoke_to(env: Env, vault_id: u64, destination: Address) -> i128 {
+        Self::require_admin(&env);
 
-            env.storage()
-                .instance()
-                .set(&DataKey::VaultData(vault_id), &vault);
-            vault_ids.push_back(vault_id);
+        let returned = Self::internal_revoke_full(&env, vault_id);
 
-            let start_time = batch_data.start_times.get(i).unwrap();
-            let cliff_duration = start_time.saturating_sub(now);
-            let vault_created = VaultCreated {
-                vault_id,
-                beneficiary: vault.owner.clone(),
-                total_amount: vault.total_amount,
-                cliff_duration,
-                start_time,
-                title: String::from_slice(&env, ""),
-            };
-            env.events()
-                .publish((Symbol::new(&env, "VaultCreated"), vault_id), vault_created);
-        }
+        Self::get_token_client(&env).transfer(
+            &env.current_contract_address(),
+            &destination,
+            &returned,
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::RevokeDestination(vault_id), &destination);
 
         let mut total_shares: i128 = env
             .storage()
             .instance()
             .get(&DataKey::TotalShares)
             .unwrap_or(0);
-        total_shares += total_amount;
+        total_shares -= returned;
         env.storage()
             .instance()
             .set(&DataKey::TotalShares, &total_shares);
 
-        let final_count = initial_count + batch_data.recipients.len() as u64;
+        env.events().publish(
+            (Symbol::new(&env, "TokensRevokedTo"), vault_id),
+            (destination, returned, env.ledger().timestamp()),
+        );
+
+        returned
+    }
+
+    // Get the destination recorded by revoke_to, if the vault was revoked that way
+    pub fn get_revoke_destination(env: Env, vault_id: u64) -> Option<Address> {
         env.storage()
             .instance()
-            .set(&DataKey::VaultCount, &final_count);
-
-        vault_ids
+            .get(&DataKey::RevokeDestination(vault_id))
     }
 
-    // Batch create vaults with full initialization
-    pub fn batch_create_vaults_full(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
+    // Admin-only: revoke a vault but pay the beneficiary what has already vested.
+    // Vested-but-unreleased tokens go to the owner; only the unvested remainder returns
+    // to the admin. Returns (paid to beneficiary, returned to admin).
+    pub fn fair_revoke(env: Env, vault_id: u64) -> (i128, i128) {
         Self::require_admin(&env);
 
-        let mut vault_ids = Vec::new(&env);
-        let initial_count: u64 = env
+        let mut vault: Vault = env
             .storage()
             .instance()
-            .get(&DataKey::VaultCount)
-            .unwrap_or(0);
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
 
-        let total_amount: i128 = batch_data.amounts.iter().sum();
-        let mut admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        if admin_balance < total_amount {
-            panic!("Insufficient admin balance for batch");
+        if vault.is_irrevocable {
+            panic!("Vault is irrevocable");
+        }
+        Self::require_not_staked(&vault);
+        if Self::is_freeze_active(&env, vault_id, &vault) {
+            panic!("Cannot revoke frozen vault");
         }
-        admin_balance -= total_amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
-
-        let now = env.ledger().timestamp();
-        for i in 0..batch_data.recipients.len() {
-            let vault_id = initial_count + i as u64 + 1;
-
-            let vault = Vault {
-                title: String::from_slice(&env, ""),
-                owner: batch_data.recipients.get(i).unwrap(),
-                delegate: None,
-                total_amount: batch_data.amounts.get(i).unwrap(),
-                released_amount: 0,
-                start_time: batch_data.start_times.get(i).unwrap(),
-                end_time: batch_data.end_times.get(i).unwrap(),
-                keeper_fee: batch_data.keeper_fees.get(i).unwrap(),
-                title: String::from_slice(&env, ""),
-                is_initialized: true,
-                is_irrevocable: false,
-                creation_time: now,
-                is_transferable: false,
-                step_duration: batch_data.step_durations.get(i).unwrap_or(0),
-                staked_amount: 0,
-                is_frozen: false,
-            };
 
-            env.storage()
-                .instance()
-                .set(&DataKey::VaultData(vault_id), &vault);
+        let unreleased = vault.total_amount - vault.released_amount;
+        if unreleased <= 0 {
+            panic!("No tokens available to revoke");
+        }
 
-            let mut user_vaults: Vec<u64> = env
-                .storage()
-                .instance()
-                .get(&DataKey::UserVaults(vault.owner.clone()))
-                .unwrap_or(Vec::new(&env));
-            user_vaults.push_back(vault_id);
-            env.storage()
-                .instance()
-                .set(&DataKey::UserVaults(vault.owner.clone()), &user_vaults);
+        let vested = if env
+            .storage()
+            .instance()
+            .has(&DataKey::VaultMilestones(vault_id))
+        {
+            Self::milestone_unlocked_amount(&env, vault_id, vault.total_amount)
+        } else {
+            Self::calculate_time_vested_amount(&env, &vault)
+        };
+        let vested_amount = (vested - vault.released_amount).clamp(0, unreleased);
+        let unvested_amount = unreleased - vested_amount;
 
-            vault_ids.push_back(vault_id);
+        vault.released_amount = vault.total_amount;
+        Self::save_vault(&env, vault_id, &vault);
 
-            let start_time = batch_data.start_times.get(i).unwrap();
-            let cliff_duration = start_time.saturating_sub(now);
-            let vault_created = VaultCreated {
-                vault_id,
-                beneficiary: vault.owner.clone(),
-                total_amount: vault.total_amount,
-                cliff_duration,
-                start_time,
-                title: String::from_slice(&env, ""),
-            };
-            env.events()
-                .publish((Symbol::new(&env, "VaultCreated"), vault_id), vault_created);
+        if vested_amount > 0 {
+            Self::get_token_client(&env).transfer(
+                &env.current_contract_address(),
+                &vault.owner,
+                &vested_amount,
+            );
+        }
+        if unvested_amount > 0 {
+            Self::settle_revoked(&env, unvested_amount);
         }
 
         let mut total_shares: i128 = env
@@ -1299,68 +3537,54 @@ impl VestingContract {
             .instance()
             .get(&DataKey::TotalShares)
             .unwrap_or(0);
-        total_shares += total_amount;
+        total_shares -= unreleased;
         env.storage()
             .instance()
             .set(&DataKey::TotalShares, &total_shares);
 
-        let final_count = initial_count + batch_data.recipients.len() as u64;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultCount, &final_count);
+        env.events().publish(
+            (Symbol::new(&env, "TokensRevoked"), vault_id),
+            TokensRevoked {
+                vault_id,
+                vested_amount,
+                unvested_amount,
+                beneficiary: vault.owner,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
 
-        vault_ids
+        (vested_amount, unvested_amount)
     }
 
-    // Get vault info (initializes if needed)
-    pub fn get_vault(env: Env, vault_id: u64) -> Vault {
-        let vault: Vault = env
-            .storage()
-            .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
-
-        if !vault.is_initialized {
-            Self::initialize_vault_metadata(&env, vault_id);
+    // Admin-only: "use it or lose it" deadline after which the vault can no longer be claimed
+    // and its unreleased balance can be swept back with sweep_expired. Zero clears it.
+    pub fn set_claim_deadline(env: Env, vault_id: u64, claim_deadline: u64) {
+        Self::require_admin(&env);
+        if !env.storage().instance().has(&DataKey::VaultData(vault_id)) {
+            panic!("Vault not found");
+        }
+        if claim_deadline == 0 {
             env.storage()
                 .instance()
-                .get(&DataKey::VaultData(vault_id))
-                .unwrap()
-                .unwrap_or_else(|| panic!("Vault not found"))
+                .remove(&DataKey::ClaimDeadline(vault_id));
         } else {
-            vault
+            env.storage()
+                .instance()
+                .set(&DataKey::ClaimDeadline(vault_id), &claim_deadline);
         }
     }
 
-    // Get user vaults (initializes all if needed)
-    pub fn get_user_vaults(env: Env, user: Address) -> Vec<u64> {
-        let vault_ids: Vec<u64> = env
-            .storage()
+    // Get the claim deadline of a vault (0 = none)
+    pub fn get_claim_deadline(env: Env, vault_id: u64) -> u64 {
+        env.storage()
             .instance()
-            .get(&DataKey::UserVaults(user.clone()))
-            .get(&DataKey::UserVaults(user))
-            .unwrap_or(Vec::new(&env));
-
-        for vault_id in vault_ids.iter() {
-            let vault: Vault = env
-                .storage()
-                .instance()
-                .get(&DataKey::VaultData(vault_id))
-                .unwrap_or_else(|| panic!("Vault not found"));
-
-            if !vault.is_initialized {
-                Self::initialize_vault_metadata(&env, vault_id);
-            }
-        }
-
-        vault_ids
+            .get(&DataKey::ClaimDeadline(vault_id))
+            .unwrap_or(0)
     }
 
-    // Revoke tokens from a vault and return them to admin
-    // Internal helper: revoke full unreleased amount from a vault and emit event.
-    // Does NOT update admin balance — caller is responsible for a single aggregated transfer.
-    fn internal_revoke_full(env: &Env, vault_id: u64) -> i128 {
-    pub fn revoke_tokens(env: Env, vault_id: u64) -> i128 {
+    // Admin-only: return the unreleased balance of a vault past its claim deadline to the
+    // treasury (or AdminBalance when no treasury is set)
+    pub fn sweep_expired(env: Env, vault_id: u64) -> i128 {
         Self::require_admin(&env);
 
         let mut vault: Vault = env
@@ -1369,85 +3593,140 @@ impl VestingContract {
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        if vault.is_irrevocable {
-            panic!("Vault is irrevocable");
+        let deadline = Self::get_claim_deadline(env.clone(), vault_id);
+        if deadline == 0 || env.ledger().timestamp() < deadline {
+            panic!("Claim deadline not reached");
         }
+        Self::require_not_staked(&vault);
 
-        let unreleased_amount = vault.total_amount - vault.released_amount;
-        if unreleased_amount <= 0 {
-        let returned = vault.total_amount - vault.released_amount;
-        if returned <= 0 {
-            panic!("No tokens available to revoke");
+        let swept = vault.total_amount - vault.released_amount;
+        if swept <= 0 {
+            panic!("Nothing to sweep");
         }
 
         vault.released_amount = vault.total_amount;
-        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        Self::save_vault(&env, vault_id, &vault);
+
+        Self::settle_reclaimed(&env, swept);
+
+        let mut total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0);
+        total_shares -= swept;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
 
-        let timestamp = env.ledger().timestamp();
         env.events().publish(
-            (Symbol::new(env, "TokensRevoked"), vault_id),
-            (unreleased_amount, timestamp),
+            (Symbol::new(&env, "ExpiredSwept"), vault_id),
+            (swept, env.ledger().timestamp()),
         );
 
-        unreleased_amount
+        swept
     }
 
-    // Admin-only: Revoke tokens from a vault and return them to admin
-    pub fn revoke_tokens(env: Env, vault_id: u64) -> i128 {
+    // Revoke a specific amount of tokens from a vault and return them to admin
+    pub fn revoke_partial(env: Env, vault_id: u64, amount: i128) -> i128 {
         Self::require_admin(&env);
 
-        let returned = Self::internal_revoke_full(&env, vault_id);
+        let returned = Self::internal_revoke_partial(&env, vault_id, amount);
+
+        // Single admin balance update (or burn) for this call
+        Self::settle_revoked(&env, returned);
+
+        returned
+    }
+
+    // Admin-only: choose whether revoked tokens are burned instead of returned to the admin pool
+    pub fn set_burn_on_revoke(env: Env, burn_on_revoke: bool, burn_address: Address) {
+        Self::require_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::BurnOnRevoke, &burn_on_revoke);
+        env.storage()
+            .instance()
+            .set(&DataKey::BurnAddress, &burn_address);
+    }
+
+    // Check whether revoked tokens are currently burned
+    pub fn is_burn_on_revoke(env: Env) -> bool {
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .get(&DataKey::BurnOnRevoke)
+            .unwrap_or(false)
+    }
+
+    // Route revoked tokens: transfer them to the burn address when burning is enabled,
+    // otherwise credit them back to AdminBalance for re-granting.
+    fn settle_revoked(env: &Env, amount: i128) {
+        if Self::is_burn_on_revoke(env.clone()) {
+            let burn_address: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::BurnAddress)
+                .unwrap_or_else(|| panic!("Burn address not set"));
+            Self::get_token_client(env).transfer(
+                &env.current_contract_address(),
+                &burn_address,
+                &amount,
+            );
+            return;
+        }
 
         let mut admin_balance: i128 = env
             .storage()
             .instance()
             .get(&DataKey::AdminBalance)
             .unwrap_or(0);
-        admin_balance += returned;
+        admin_balance += amount;
         env.storage()
             .instance()
             .set(&DataKey::AdminBalance, &admin_balance);
+    }
 
-        let mut total_shares: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalShares)
-            .unwrap_or(0);
-        total_shares -= returned;
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalShares, &total_shares);
+    // Admin-only: send clawed-back and swept tokens to `treasury` instead of AdminBalance
+    pub fn set_treasury(env: Env, treasury: Address) {
+        Self::require_admin(&env);
+        if treasury == env.current_contract_address() {
+            panic!("Treasury cannot be the contract address");
+        }
+
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
 
-        let timestamp = env.ledger().timestamp();
         env.events().publish(
-            (Symbol::new(&env, "TokensRevoked"), vault_id),
-            (returned, timestamp),
+            (Symbol::new(&env, "TreasuryUpdated"),),
+            treasury,
         );
-
-        returned
     }
 
-    // Revoke a specific amount of tokens from a vault and return them to admin
-    pub fn revoke_partial(env: Env, vault_id: u64, amount: i128) -> i128 {
-        Self::require_admin(&env);
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
 
-        let returned = Self::internal_revoke_partial(&env, vault_id, amount);
+    // Route reclaimed tokens: transfer them to the treasury when one is set,
+    // otherwise credit them back to AdminBalance.
+    fn settle_reclaimed(env: &Env, amount: i128) {
+        if let Some(treasury) = Self::get_treasury(env.clone()) {
+            Self::get_token_client(env).transfer(
+                &env.current_contract_address(),
+                &treasury,
+                &amount,
+            );
+            return;
+        }
 
-        // Single admin balance update for this call
         let mut admin_balance: i128 = env
             .storage()
             .instance()
             .get(&DataKey::AdminBalance)
             .unwrap_or(0);
-        admin_balance += returned;
+        admin_balance += amount;
         env.storage()
             .instance()
             .set(&DataKey::AdminBalance, &admin_balance);
-
-        returned
     }
 
     // Internal helper: revoke a specific amount from a vault and emit event.
@@ -1459,15 +3738,10 @@ impl VestingContract {
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        let mut vault: Vault = env
-            .storage()
-            .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
-
         if vault.is_irrevocable {
             panic!("Vault is irrevocable");
         }
+        Self::require_not_staked(&vault);
 
         let unvested_balance = vault.total_amount - vault.released_amount;
         if amount <= 0 {
@@ -1478,22 +3752,7 @@ impl VestingContract {
         }
 
         vault.released_amount += amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
-
-        let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (Symbol::new(env, "TokensRevoked"), vault_id),
-        let mut admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        admin_balance += amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+        Self::save_vault(env, vault_id, &vault);
 
         let mut total_shares: i128 = env
             .storage()
@@ -1505,16 +3764,18 @@ impl VestingContract {
             .instance()
             .set(&DataKey::TotalShares, &total_shares);
 
+        // Distinct from full revokes (TokensRevoked); carries what is still revocable
+        let remaining_unvested = vault.total_amount - vault.released_amount;
         let timestamp = env.ledger().timestamp();
         env.events().publish(
-            (Symbol::new(&env, "TokensRevoked"), vault_id),
-            (amount, timestamp),
+            (Symbol::new(env, "PartialRevoke"), vault_id),
+            (amount, remaining_unvested, timestamp),
         );
 
         amount
     }
 
-    // Admin-only: Revoke many vaults in a single call and credit the admin once.
+    // Admin-only: Revoke many vaults in a single call and settle the total once.
     pub fn batch_revoke(env: Env, vault_ids: Vec<u64>) -> i128 {
         Self::require_admin(&env);
 
@@ -1529,6 +3790,7 @@ impl VestingContract {
             if vault.is_irrevocable {
                 panic!("Vault is irrevocable");
             }
+            Self::require_not_staked(&vault);
 
             let returned = vault.total_amount - vault.released_amount;
             if returned <= 0 {
@@ -1536,9 +3798,7 @@ impl VestingContract {
             }
 
             vault.released_amount = vault.total_amount;
-            env.storage()
-                .instance()
-                .set(&DataKey::VaultData(vault_id), &vault);
+            Self::save_vault(&env, vault_id, &vault);
             total_returned += returned;
 
             let timestamp = env.ledger().timestamp();
@@ -1548,15 +3808,10 @@ impl VestingContract {
             );
         }
 
-        let mut admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        admin_balance += total_returned;
-        env.storage()
-            .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+        // Single admin balance update (or burn) for the whole batch
+        if total_returned > 0 {
+            Self::settle_revoked(&env, total_returned);
+        }
 
         let mut total_shares: i128 = env
             .storage()
@@ -1597,21 +3852,12 @@ impl VestingContract {
         if vault.released_amount > 0 {
             panic!("Tokens already claimed");
         }
+        Self::require_not_staked(&vault);
 
-        let mut admin_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        admin_balance += vault.total_amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+        Self::settle_reclaimed(&env, vault.total_amount);
 
         vault.released_amount = vault.total_amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+        Self::save_vault(&env, vault_id, &vault);
 
         let mut total_shares: i128 = env
             .storage()
@@ -1633,30 +3879,133 @@ impl VestingContract {
 
     // Transfer vault ownership to another beneficiary (if transferable)
     pub fn transfer_vault(env: Env, vault_id: u64, new_beneficiary: Address) {
-        let mut vault: Vault = env
+        let vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        if !vault.is_initialized {
-            panic!("Vault not initialized");
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
+        }
+        if Self::is_transfer_acceptance_required(env.clone(), vault_id) {
+            panic!("Transfer requires recipient acceptance");
+        }
+
+        vault.owner.require_auth();
+        Self::require_valid_new_owner(&env, &vault, &new_beneficiary);
+
+        Self::reassign_vault_owner(&env, vault_id, vault, new_beneficiary);
+    }
+
+    // Admin-only: require the recipient to accept transfers of this vault (offer_transfer /
+    // accept_transfer) instead of allowing the owner's instant transfer_vault.
+    pub fn set_transfer_acceptance_required(env: Env, vault_id: u64, required: bool) {
+        Self::require_admin(&env);
+        if !env.storage().instance().has(&DataKey::VaultData(vault_id)) {
+            panic!("Vault not found");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::TransferAcceptanceRequired(vault_id), &required);
+    }
+
+    pub fn is_transfer_acceptance_required(env: Env, vault_id: u64) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::TransferAcceptanceRequired(vault_id))
+            .unwrap_or(false)
+    }
+
+    // First step of a two-step transfer: the owner offers the vault to `to`.
+    // A new offer replaces any pending one.
+    pub fn offer_transfer(env: Env, vault_id: u64, to: Address) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
+        }
+
+        vault.owner.require_auth();
+        Self::require_valid_new_owner(&env, &vault, &to);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingTransfer(vault_id), &(vault.owner.clone(), to.clone()));
+
+        env.events().publish(
+            (Symbol::new(&env, "TransferOffered"), vault_id),
+            (vault.owner, to),
+        );
+    }
+
+    // Second step: the offered recipient accepts and becomes the owner
+    pub fn accept_transfer(env: Env, vault_id: u64) {
+        let (from, to): (Address, Address) = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingTransfer(vault_id))
+            .unwrap_or_else(|| panic!("No pending transfer"));
+        to.require_auth();
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        // The owner changed some other way (rotation, admin transfer) since the offer was made
+        if vault.owner != from {
+            panic!("Transfer offer is stale");
+        }
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
+        }
+
+        env.storage()
+            .instance()
+            .remove(&DataKey::PendingTransfer(vault_id));
+        Self::reassign_vault_owner(&env, vault_id, vault, to);
+    }
+
+    // Get the pending (from, to) transfer offer for a vault, if any
+    pub fn get_pending_transfer(env: Env, vault_id: u64) -> Option<(Address, Address)> {
+        env.storage()
+            .instance()
+            .get(&DataKey::PendingTransfer(vault_id))
+    }
+
+    // A vault can't be handed to the contract itself (funds would be stuck) or to its current owner
+    fn require_valid_new_owner(env: &Env, vault: &Vault, new_owner: &Address) {
+        if *new_owner == env.current_contract_address() {
+            panic!("Cannot transfer to the contract address");
         }
-        if !vault.is_transferable {
-            panic!("Vault is non-transferable");
+        if *new_owner == vault.owner {
+            panic!("New owner must differ from current owner");
         }
+    }
 
-        vault.owner.require_auth();
-
+    // Move an initialized vault to `new_owner`, updating both owners' UserVaults and
+    // clearing the delegate
+    fn reassign_vault_owner(env: &Env, vault_id: u64, mut vault: Vault, new_owner: Address) {
         let old_owner = vault.owner.clone();
 
         let old_user_vaults: Vec<u64> = env
             .storage()
             .instance()
             .get(&DataKey::UserVaults(old_owner.clone()))
-            .unwrap_or(Vec::new(&env));
+            .unwrap_or(Vec::new(env));
 
-        let mut new_old_user_vaults = Vec::new(&env);
+        let mut new_old_user_vaults = Vec::new(env);
         for id in old_user_vaults.iter() {
             if id != vault_id {
                 new_old_user_vaults.push_back(id);
@@ -1665,34 +4014,29 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::UserVaults(old_owner.clone()), &new_old_user_vaults);
-        env.storage().instance().set(
-            &DataKey::UserVaults(old_owner.clone()),
-            &new_old_user_vaults,
-        );
 
         let mut new_user_vaults: Vec<u64> = env
             .storage()
             .instance()
-            .get(&DataKey::UserVaults(new_beneficiary.clone()))
-            .unwrap_or(Vec::new(&env));
+            .get(&DataKey::UserVaults(new_owner.clone()))
+            .unwrap_or(Vec::new(env));
         new_user_vaults.push_back(vault_id);
         env.storage()
             .instance()
-            .set(&DataKey::UserVaults(new_beneficiary.clone()), &new_user_vaults);
-        env.storage().instance().set(
-            &DataKey::UserVaults(new_beneficiary.clone()),
-            &new_user_vaults,
-        );
+            .set(&DataKey::UserVaults(new_owner.clone()), &new_user_vaults);
 
-        vault.owner = new_beneficiary.clone();
+        vault.owner = new_owner.clone();
         vault.delegate = None;
+        vault.delegate_permissions = 0;
+        Self::save_vault(env, vault_id, &vault);
+        // The backup address was designated by the previous owner
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .remove(&DataKey::RecoveryAddress(vault_id));
 
         env.events().publish(
-            (Symbol::new(&env, "BeneficiaryUpdated"), vault_id),
-            (old_owner, new_beneficiary),
+            (Symbol::new(env, "BeneficiaryUpdated"), vault_id),
+            (old_owner, new_owner),
         );
     }
 
@@ -1709,6 +4053,7 @@ impl VestingContract {
         }
 
         vault.owner.require_auth();
+        Self::require_valid_new_owner(&env, &vault, &new_address);
 
         let old_owner = vault.owner.clone();
 
@@ -1744,9 +4089,8 @@ impl VestingContract {
 
         vault.owner = new_address.clone();
         vault.delegate = None;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+        vault.delegate_permissions = 0;
+        Self::save_vault(&env, vault_id, &vault);
 
         env.events().publish(
             (Symbol::new(&env, "BeneficiaryRotated"), vault_id),
@@ -1754,6 +4098,96 @@ impl VestingContract {
         );
     }
 
+    // Owner designates a backup address that can take over the vault after a long period
+    // without claims (see recover_vault). Counts as activity on the vault.
+    pub fn set_recovery_address(env: Env, vault_id: u64, recovery: Address) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        vault.owner.require_auth();
+        Self::require_valid_new_owner(&env, &vault, &recovery);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RecoveryAddress(vault_id), &recovery);
+        env.storage()
+            .instance()
+            .set(&DataKey::LastActivity(vault_id), &env.ledger().timestamp());
+    }
+
+    pub fn get_recovery_address(env: Env, vault_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::RecoveryAddress(vault_id))
+    }
+
+    // Admin-only: seconds without claim activity before recover_vault is allowed
+    pub fn set_recovery_inactivity_period(env: Env, period_seconds: u64) {
+        Self::require_admin(&env);
+        if period_seconds == 0 {
+            panic!("Inactivity period must be positive");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::RecoveryInactivityPeriod, &period_seconds);
+    }
+
+    pub fn get_recovery_inactivity_period(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RecoveryInactivityPeriod)
+            .unwrap_or(DEFAULT_RECOVERY_INACTIVITY)
+    }
+
+    // Last claim (or recovery designation) on the vault, falling back to its creation time
+    pub fn get_last_activity(env: Env, vault_id: u64) -> u64 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        env.storage()
+            .instance()
+            .get(&DataKey::LastActivity(vault_id))
+            .unwrap_or(vault.creation_time)
+    }
+
+    // Dead-man switch: the recovery address takes ownership once the vault has been
+    // inactive for the configured period
+    pub fn recover_vault(env: Env, vault_id: u64) {
+        let recovery: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::RecoveryAddress(vault_id))
+            .unwrap_or_else(|| panic!("No recovery address set"));
+        recovery.require_auth();
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let last_activity = Self::get_last_activity(env.clone(), vault_id);
+        let period = Self::get_recovery_inactivity_period(env.clone());
+        if env.ledger().timestamp() < last_activity.saturating_add(period) {
+            panic!("Vault is still active");
+        }
+
+        Self::reassign_vault_owner(&env, vault_id, vault, recovery.clone());
+        env.events().publish(
+            (Symbol::new(&env, "VaultRecovered"), vault_id),
+            recovery,
+        );
+    }
+
     // Set the whitelisted staking contract address
     pub fn set_staking_contract(env: Env, contract: Address) {
         Self::require_admin(&env);
@@ -1762,8 +4196,236 @@ impl VestingContract {
             .set(&Symbol::new(&env, "StakingContract"), &contract);
     }
 
+    pub fn get_staking_contract(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "StakingContract"))
+    }
+
+    // Admin-only: cap the share of each vault's total_amount that may be staked at once
+    pub fn set_max_stake_bps(env: Env, max_stake_bps: u32) {
+        Self::require_admin(&env);
+        if max_stake_bps > 10_000 {
+            panic!("Max stake bps cannot exceed 10000");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxStakeBps, &max_stake_bps);
+    }
+
+    // Admin-only: approve a validator. Once any validator is approved, stake_tokens
+    // only accepts validators on the list.
+    pub fn approve_validator(env: Env, validator: Address) {
+        Self::require_admin(&env);
+        let mut validators: Map<Address, bool> = env
+            .storage()
+            .instance()
+            .get(&DataKey::ApprovedValidators)
+            .unwrap_or(Map::new(&env));
+        validators.set(validator, true);
+        env.storage()
+            .instance()
+            .set(&DataKey::ApprovedValidators, &validators);
+    }
+
+    // Admin-only: remove a validator from the approved list
+    pub fn remove_validator(env: Env, validator: Address) {
+        Self::require_admin(&env);
+        let mut validators: Map<Address, bool> = env
+            .storage()
+            .instance()
+            .get(&DataKey::ApprovedValidators)
+            .unwrap_or(Map::new(&env));
+        validators.remove(validator);
+        env.storage()
+            .instance()
+            .set(&DataKey::ApprovedValidators, &validators);
+    }
+
     // Stake unvested tokens to the whitelisted staking contract
     pub fn stake_tokens(env: Env, vault_id: u64, amount: i128, validator: Address) {
+        Self::internal_stake(env, vault_id, amount, validator, false)
+    }
+
+    // Owner pulls `amount` back from the staking contract into the vault's liquid balance
+    pub fn unstake_tokens(env: Env, vault_id: u64, amount: i128) {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.owner.require_auth();
+
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        if amount > vault.staked_amount {
+            panic!("Amount exceeds staked balance");
+        }
+
+        let staking_contract: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, "StakingContract"))
+            .expect("Staking contract not set");
+        let args = vec![&env, vault_id.into_val(&env), amount.into_val(&env)];
+        env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "unstake"), args);
+        Self::drain_validator_stakes(&env, vault_id, amount, &Vec::new(&env));
+
+        Self::record_unstake(&env, vault_id, &mut vault, amount);
+        Self::save_vault(&env, vault_id, &vault);
+    }
+
+    // Book `amount` coming back from the staking contract against the vault and TotalStaked
+    fn record_unstake(env: &Env, vault_id: u64, vault: &mut Vault, amount: i128) {
+        vault.staked_amount -= amount;
+
+        let total_staked: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalStaked)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalStaked, &(total_staked - amount));
+
+        env.events().publish(
+            (Symbol::new(env, "TokensUnstaked"), vault_id),
+            amount,
+        );
+    }
+
+    // Revoking while tokens sit in the staking contract would orphan them from TotalStaked
+    fn require_not_staked(vault: &Vault) {
+        if vault.staked_amount > 0 {
+            panic!("Unstake before revoking");
+        }
+    }
+
+    // Stake on the owner's behalf as a delegate holding DELEGATE_STAKE
+    pub fn stake_as_delegate(env: Env, vault_id: u64, amount: i128, validator: Address) {
+        Self::internal_stake(env, vault_id, amount, validator, true)
+    }
+
+    // Pull staking rewards earned by the vault's stake and pay them to the owner
+    pub fn claim_staking_rewards(env: Env, vault_id: u64, validator: Address) -> i128 {
+        Self::internal_claim_staking_rewards(env, vault_id, validator, false)
+    }
+
+    // Pull staking rewards for the owner as a delegate holding DELEGATE_STAKE
+    pub fn claim_rewards_as_delegate(env: Env, vault_id: u64, validator: Address) -> i128 {
+        Self::internal_claim_staking_rewards(env, vault_id, validator, true)
+    }
+
+    // The staking contract's `claim_rewards(vault_id, validator) -> i128` must transfer the
+    // rewards to this contract; only tokens actually received are forwarded to the owner.
+    fn internal_claim_staking_rewards(
+        env: Env,
+        vault_id: u64,
+        validator: Address,
+        as_delegate: bool,
+    ) -> i128 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        if as_delegate {
+            Self::require_delegate_permission(&vault, DELEGATE_STAKE);
+        } else {
+            vault.owner.require_auth();
+        }
+
+        let staking_contract: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, "StakingContract"))
+            .expect("Staking contract not set");
+
+        let token_client = Self::get_token_client(&env);
+        let balance_before = token_client.balance(&env.current_contract_address());
+
+        let args = vec![&env, vault_id.into_val(&env), validator.into_val(&env)];
+        let rewards: i128 = env.invoke_contract(
+            &staking_contract,
+            &Symbol::new(&env, "claim_rewards"),
+            args,
+        );
+        if rewards <= 0 {
+            return 0;
+        }
+
+        let received = token_client.balance(&env.current_contract_address()) - balance_before;
+        if received < rewards {
+            panic!("Staking rewards not received");
+        }
+
+        token_client.transfer(&env.current_contract_address(), &vault.owner, &rewards);
+
+        env.events().publish(
+            (Symbol::new(&env, "StakingRewardsClaimed"), vault_id),
+            (rewards, validator),
+        );
+        rewards
+    }
+
+    // Per-validator breakdown of a vault's staked amount
+    pub fn get_validator_stakes(env: Env, vault_id: u64) -> Map<Address, i128> {
+        env.storage()
+            .instance()
+            .get(&DataKey::ValidatorStakes(vault_id))
+            .unwrap_or(Map::new(&env))
+    }
+
+    // Take up to `amount` off the vault's per-validator stakes, validators in `order` first and
+    // then the rest in map order. Returns the (validator, amount) pairs drained.
+    fn drain_validator_stakes(
+        env: &Env,
+        vault_id: u64,
+        amount: i128,
+        order: &Vec<Address>,
+    ) -> Vec<(Address, i128)> {
+        let mut stakes = Self::get_validator_stakes(env.clone(), vault_id);
+        let mut sequence = order.clone();
+        for validator in stakes.keys().iter() {
+            if !order.contains(&validator) {
+                sequence.push_back(validator);
+            }
+        }
+
+        let mut drained = Vec::new(env);
+        let mut remaining = amount;
+        for validator in sequence.iter() {
+            if remaining == 0 {
+                break;
+            }
+            let staked = stakes.get(validator.clone()).unwrap_or(0);
+            let take = staked.min(remaining);
+            if take <= 0 {
+                continue;
+            }
+            if staked == take {
+                stakes.remove(validator.clone());
+            } else {
+                stakes.set(validator.clone(), staked - take);
+            }
+            drained.push_back((validator, take));
+            remaining -= take;
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ValidatorStakes(vault_id), &stakes);
+        drained
+    }
+
+    fn internal_stake(env: Env, vault_id: u64, amount: i128, validator: Address, as_delegate: bool) {
         let mut vault: Vault = env
             .storage()
             .instance()
@@ -1774,7 +4436,11 @@ impl VestingContract {
             panic!("Vault not initialized");
         }
 
-        vault.owner.require_auth();
+        if as_delegate {
+            Self::require_delegate_permission(&vault, DELEGATE_STAKE);
+        } else {
+            vault.owner.require_auth();
+        }
 
         let available = vault.total_amount - vault.released_amount - vault.staked_amount;
         if amount <= 0 {
@@ -1784,6 +4450,26 @@ impl VestingContract {
             panic!("Insufficient funds to stake");
         }
 
+        if let Some(max_stake_bps) = env
+            .storage()
+            .instance()
+            .get::<DataKey, u32>(&DataKey::MaxStakeBps)
+        {
+            let max_stake = vault.total_amount * max_stake_bps as i128 / 10_000;
+            if vault.staked_amount + amount > max_stake {
+                panic!("Stake exceeds max stake bps");
+            }
+        }
+        if let Some(validators) = env
+            .storage()
+            .instance()
+            .get::<DataKey, Map<Address, bool>>(&DataKey::ApprovedValidators)
+        {
+            if !validators.get(validator.clone()).unwrap_or(false) {
+                panic!("Validator not approved");
+            }
+        }
+
         let staking_contract: Address = env
             .storage()
             .instance()
@@ -1799,6 +4485,11 @@ impl VestingContract {
         env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "stake"), args);
 
         vault.staked_amount += amount;
+        let mut stakes = Self::get_validator_stakes(env.clone(), vault_id);
+        stakes.set(validator.clone(), stakes.get(validator.clone()).unwrap_or(0) + amount);
+        env.storage()
+            .instance()
+            .set(&DataKey::ValidatorStakes(vault_id), &stakes);
 
         let mut total_staked: i128 = env
             .storage()
@@ -1810,9 +4501,12 @@ impl VestingContract {
             .instance()
             .set(&DataKey::TotalStaked, &total_staked);
 
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+        Self::save_vault(&env, vault_id, &vault);
+
+        env.events().publish(
+            (Symbol::new(&env, "TokensStaked"), vault_id),
+            (amount, validator),
+        );
     }
 
     // Mark a vault as irrevocable to prevent admin withdrawal
@@ -1830,9 +4524,7 @@ impl VestingContract {
         }
 
         vault.is_irrevocable = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+        Self::save_vault(&env, vault_id, &vault);
 
         let timestamp = env.ledger().timestamp();
         env.events().publish(
@@ -1846,10 +4538,50 @@ impl VestingContract {
         let vault: Vault = env
             .storage()
             .instance()
-            .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.is_irrevocable
+    }
+
+    // Admin-only, one-time migration for deployments that predate the running totals:
+    // recompute TotalLocked / TotalClaimed from a full vault scan.
+    pub fn backfill_running_totals(env: Env) -> (i128, i128) {
+        Self::require_admin(&env);
+        if env
+            .storage()
+            .instance()
+            .get(&DataKey::RunningTotalsBackfilled)
+            .unwrap_or(false)
+        {
+            panic!("Running totals already backfilled");
+        }
+
+        let vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        let mut total_locked: i128 = 0;
+        let mut total_claimed: i128 = 0;
+        for vault_id in 1..=vault_count {
+            if let Some(vault) = env
+                .storage()
+                .instance()
+                .get::<DataKey, Vault>(&DataKey::VaultData(vault_id))
+            {
+                total_locked += vault.total_amount - vault.released_amount;
+                total_claimed += vault.released_amount;
+            }
+        }
+
+        env.storage().instance().set(&DataKey::TotalLocked, &total_locked);
+        env.storage().instance().set(&DataKey::TotalClaimed, &total_claimed);
+        env.storage()
+            .instance()
+            .set(&DataKey::RunningTotalsBackfilled, &true);
 
-        vault.is_irrevocable
+        (total_locked, total_claimed)
     }
 
     // Get contract state for invariant checking
@@ -1860,24 +4592,17 @@ impl VestingContract {
             .get(&DataKey::AdminBalance)
             .unwrap_or(0);
 
-        let vault_count: u64 = env
+        // Running totals maintained by save_vault, so this stays O(1) as vaults accumulate
+        let total_locked: i128 = env
             .storage()
             .instance()
-            .get(&DataKey::VaultCount)
+            .get(&DataKey::TotalLocked)
+            .unwrap_or(0);
+        let total_claimed: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalClaimed)
             .unwrap_or(0);
-        let mut total_locked = 0i128;
-        let mut total_claimed = 0i128;
-
-        for i in 1..=vault_count {
-            if let Some(vault) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Vault>(&DataKey::VaultData(i))
-            {
-                total_locked += vault.total_amount - vault.released_amount;
-                total_claimed += vault.released_amount;
-            }
-        }
 
         (total_locked, total_claimed, admin_balance)
     }
@@ -1904,11 +4629,40 @@ impl VestingContract {
         net_paid_out >= 0
     }
 
+    // Admin-only: record the token balance held above principal (admin balance + unreleased
+    // vault amounts) as accrued yield. Re-harvesting overwrites the figure rather than adding to it.
+    pub fn harvest(env: Env) -> i128 {
+        Self::require_admin(&env);
+
+        let token_client = Self::get_token_client(&env);
+        let current_balance = token_client.balance(&env.current_contract_address());
+        let (total_locked, _total_claimed, admin_balance) = Self::get_contract_state(env.clone());
+
+        let accrued_yield = (current_balance - admin_balance - total_locked).max(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::AccruedYield, &accrued_yield);
+
+        env.events().publish(
+            (Symbol::new(&env, "YieldHarvested"),),
+            (accrued_yield, env.ledger().timestamp()),
+        );
+
+        accrued_yield
+    }
+
+    // Get the yield recorded by the last harvest
+    pub fn get_accrued_yield(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::AccruedYield)
+            .unwrap_or(0)
+    }
+
     // --- Auto-Claim Logic ---
 
     // Calculate currently claimable tokens based on linear vesting
     pub fn get_claimable_amount(env: Env, vault_id: u64) -> i128 {
-        let vault: Vault = env.storage().instance()
         let vault: Vault = env
             .storage()
             .instance()
@@ -1924,24 +4678,280 @@ impl VestingContract {
         }
     }
 
+    // Milestone-aware claimable amount per vault id, in input order. Missing vaults report -1
+    // instead of failing the whole query. At most MAX_PAGE_SIZE ids per call.
+    pub fn get_claimable_batch(env: Env, vault_ids: Vec<u64>) -> Vec<i128> {
+        if vault_ids.len() > MAX_PAGE_SIZE {
+            panic!("Too many vault ids");
+        }
+
+        let mut amounts = Vec::new(&env);
+        for vault_id in vault_ids.iter() {
+            let vault: Vault = match env.storage().instance().get(&DataKey::VaultData(vault_id)) {
+                Some(vault) => vault,
+                None => {
+                    amounts.push_back(-1);
+                    continue;
+                }
+            };
+
+            let unlocked = Self::vested_amount(&env, vault_id, &vault);
+            amounts.push_back((unlocked - vault.released_amount).max(0));
+        }
+        amounts
+    }
+
+    // Amount unlocked so far: the milestone schedule if one is set, else time vesting
+    fn vested_amount(env: &Env, vault_id: u64, vault: &Vault) -> i128 {
+        if env
+            .storage()
+            .instance()
+            .has(&DataKey::VaultMilestones(vault_id))
+        {
+            Self::milestone_unlocked_amount(env, vault_id, vault.total_amount)
+        } else {
+            Self::calculate_time_vested_amount(env, vault)
+        }
+    }
+
+    // Whether a claim on this vault would succeed right now: token set, contract not paused,
+    // vault initialized (or lazily initializable), not frozen, before any claim deadline,
+    // and something unlocked.
+    pub fn is_claim_ready(env: Env, vault_id: u64) -> bool {
+        let vault: Vault = match env.storage().instance().get(&DataKey::VaultData(vault_id)) {
+            Some(vault) => vault,
+            None => return false,
+        };
+
+        if !env.storage().instance().has(&DataKey::Token)
+            || Self::is_paused(env.clone())
+            || (!vault.is_initialized
+                && env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::IsDeprecated)
+                    .unwrap_or(false))
+            || Self::is_freeze_active(&env, vault_id, &vault)
+        {
+            return false;
+        }
+
+        let deadline = Self::get_claim_deadline(env.clone(), vault_id);
+        if deadline != 0 && env.ledger().timestamp() >= deadline {
+            return false;
+        }
+
+        let unlocked = if env
+            .storage()
+            .instance()
+            .has(&DataKey::VaultMilestones(vault_id))
+        {
+            Self::milestone_unlocked_amount(&env, vault_id, vault.total_amount)
+        } else {
+            Self::calculate_time_vested_amount(&env, &vault)
+        };
+        unlocked > vault.released_amount
+    }
+
+    // Next time-based unlock as (timestamp, amount). Step vaults return the next step boundary
+    // and the tranche vesting at it, or (end_time, remaining) on the last step. Linear vaults
+    // return (now, tokens vesting per second). Fully vested vaults return (end_time, 0).
+    pub fn next_unlock(env: Env, vault_id: u64) -> (u64, i128) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let now = env.ledger().timestamp();
+        if now >= vault.end_time {
+            return (vault.end_time, 0);
+        }
+        let vested_now = Self::calculate_time_vested_amount_at(&vault, now);
+
+        if vault.step_duration == 0 {
+            let duration = (vault.end_time - vault.start_time) as i128;
+            let rate = Self::mul_div_rounded(vault.total_amount, 1, duration);
+            return (now.max(vault.start_time), rate);
+        }
+
+        let phased = now
+            .saturating_sub(vault.start_time)
+            .saturating_sub(vault.step_offset);
+        let next_step = phased / vault.step_duration + 1;
+        let boundary = vault.start_time + vault.step_offset + next_step * vault.step_duration;
+        if boundary >= vault.end_time {
+            return (vault.end_time, vault.total_amount - vested_now);
+        }
+
+        let vested_at_boundary = Self::calculate_time_vested_amount_at(&vault, boundary);
+        (boundary, vested_at_boundary - vested_now)
+    }
+
+    // Project the claimable amount at an arbitrary timestamp without mutating state
+    pub fn claimable_at(env: Env, vault_id: u64, at_timestamp: u64) -> i128 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if at_timestamp < vault.start_time {
+            return 0;
+        }
+
+        let vested = Self::calculate_time_vested_amount_at(&vault, at_timestamp);
+
+        if vested > vault.released_amount {
+            vested - vault.released_amount
+        } else {
+            0
+        }
+    }
+
+    // Compact view for light clients: (owner, total_amount, released_amount, claimable_now, is_frozen)
+    pub fn get_vault_summary(env: Env, vault_id: u64) -> (Address, i128, i128, i128, bool) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let claimable = Self::get_claimable_amount(env.clone(), vault_id);
+        let is_frozen = Self::is_freeze_active(&env, vault_id, &vault);
+
+        (
+            vault.owner,
+            vault.total_amount,
+            vault.released_amount,
+            claimable,
+            is_frozen,
+        )
+    }
+
+    // Timestamp at which the vault reaches 100% vested. Always end_time for linear vaults;
+    // for step vaults, the last step boundary before end_time if rounding already reaches the total.
+    pub fn get_fully_vested_at(env: Env, vault_id: u64) -> u64 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let span = vault.end_time.saturating_sub(vault.start_time);
+        if vault.step_duration == 0 || span <= vault.step_offset {
+            return vault.end_time;
+        }
+
+        let steps_before_end = (span - vault.step_offset - 1) / vault.step_duration;
+        let last_boundary =
+            vault.start_time + vault.step_offset + steps_before_end * vault.step_duration;
+        if steps_before_end > 0
+            && Self::calculate_time_vested_amount_at(&vault, last_boundary) >= vault.total_amount
+        {
+            last_boundary
+        } else {
+            vault.end_time
+        }
+    }
+
+    // Amount not yet vested by time (total_amount minus the time-vested amount)
+    pub fn get_remaining_locked(env: Env, vault_id: u64) -> i128 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.total_amount - Self::calculate_time_vested_amount(&env, &vault)
+    }
+
+    // Vested share of the vault in basis points (0..=10000), based on time vesting
+    pub fn get_vested_bps(env: Env, vault_id: u64) -> u32 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let vested = Self::calculate_time_vested_amount(&env, &vault);
+        Self::amount_to_bps(&env, &vault, vested)
+    }
+
+    // Claimed share of the vault in basis points (0..=10000)
+    pub fn get_claimed_bps(env: Env, vault_id: u64) -> u32 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        Self::amount_to_bps(&env, &vault, vault.released_amount)
+    }
+
+    // amount / total_amount in basis points, clamped to 10000.
+    // Zero-total vaults count as fully done once vesting has started.
+    fn amount_to_bps(env: &Env, vault: &Vault, amount: i128) -> u32 {
+        if vault.total_amount <= 0 {
+            return if env.ledger().timestamp() > vault.start_time {
+                10_000
+            } else {
+                0
+            };
+        }
+
+        let bps = amount * 10_000 / vault.total_amount;
+        bps.clamp(0, 10_000) as u32
+    }
+
     // Auto-claim function that anyone can call.
     // Tokens go to beneficiary, but keeper earns a fee.
-    pub fn auto_claim(env: Env, vault_id: u64, keeper: Address) {
-        let mut vault: Vault = env.storage().instance()
+    // `min_keeper_tokens` lets the keeper abort if the token payout after yield distribution
+    // would not cover their costs.
+    // Whether auto_claim would currently succeed for this vault (ignoring the keeper's own
+    // minimum), so keepers can skip vaults instead of submitting transactions that revert
+    pub fn can_auto_claim(env: Env, vault_id: u64) -> bool {
+        let vault: Vault = match env.storage().instance().get(&DataKey::VaultData(vault_id)) {
+            Some(vault) => vault,
+            None => return false,
+        };
+
+        if !env.storage().instance().has(&DataKey::Token)
+            || Self::is_paused(env.clone())
+            || !vault.is_initialized
+            || Self::is_freeze_active(&env, vault_id, &vault)
+        {
+            return false;
+        }
+
+        let deadline = Self::get_claim_deadline(env.clone(), vault_id);
+        if deadline != 0 && env.ledger().timestamp() >= deadline {
+            return false;
+        }
+
+        let claimable = Self::get_claimable_amount(env.clone(), vault_id);
+        let final_dust = claimable > 0 && env.ledger().timestamp() >= vault.end_time;
+        claimable > vault.keeper_fee || final_dust
+    }
+
+    pub fn auto_claim(env: Env, vault_id: u64, keeper: Address, min_keeper_tokens: i128) {
         if Self::is_paused(env.clone()) {
             panic!("Contract is paused - all withdrawals are disabled");
         }
 
+        keeper.require_auth();
+
         let mut vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        // Check if vault is frozen
-        if vault.is_frozen {
+        // Check if vault is frozen (timed freezes lift automatically)
+        if Self::is_freeze_active(&env, vault_id, &vault) {
             panic!("Vault is frozen - claims are disabled");
         }
+        Self::require_before_claim_deadline(&env, vault_id);
 
         if !vault.is_initialized {
             panic!("Vault not initialized");
@@ -1949,17 +4959,19 @@ impl VestingContract {
 
         let claimable = Self::get_claimable_amount(env.clone(), vault_id);
 
-        
+        // Once fully vested, a final tranche too small to cover the fee is released fee-free
+        // so it can't be stranded
+        let final_dust = claimable > 0
+            && claimable <= vault.keeper_fee
+            && env.ledger().timestamp() >= vault.end_time;
+
         // Ensure there's enough to cover the fee and something left for beneficiary
-        if claimable <= vault.keeper_fee {
+        if claimable <= vault.keeper_fee && !final_dust {
             panic!("Insufficient claimable tokens to cover fee");
         }
 
-        let beneficiary_amount = claimable - vault.keeper_fee;
-        let keeper_fee = vault.keeper_fee;
-
-        vault.released_amount += claimable;
-        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        let keeper_fee = if final_dust { 0 } else { vault.keeper_fee };
+        let beneficiary_amount = claimable - keeper_fee;
 
         // YIELD DISTRIBUTION - only vault-owned portion
         let token_client = Self::get_token_client(&env);
@@ -1983,17 +4995,21 @@ impl VestingContract {
         let liquid_shares = total_shares - total_staked;
 
         let vault_portion = (current_balance - admin_balance).max(0);
+        let is_yield_bearing = Self::is_yield_bearing(env.clone());
 
-        let beneficiary_tokens = if liquid_shares > 0 {
+        let beneficiary_tokens = if is_yield_bearing && liquid_shares > 0 {
             (beneficiary_amount * vault_portion) / liquid_shares
         } else {
             beneficiary_amount
         };
-        let keeper_tokens = if liquid_shares > 0 {
+        let keeper_tokens = if is_yield_bearing && liquid_shares > 0 {
             (keeper_fee * vault_portion) / liquid_shares
         } else {
             keeper_fee
         };
+        if keeper_tokens < min_keeper_tokens {
+            panic!("Keeper payout below minimum");
+        }
 
         vault.released_amount += claimable;
         let mut updated_total_shares = total_shares;
@@ -2001,16 +5017,18 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::TotalShares, &updated_total_shares);
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+        Self::save_vault(&env, vault_id, &vault);
 
+        Self::record_outflow(&env, beneficiary_tokens + keeper_tokens);
+        Self::record_owner_claim(&env, vault_id, &vault.owner);
+        let owner_tokens = Self::take_protocol_fee(&env, &token_client, vault_id, beneficiary_tokens);
         token_client.transfer(
             &env.current_contract_address(),
             &vault.owner,
-            &beneficiary_tokens,
+            &owner_tokens,
         );
         token_client.transfer(&env.current_contract_address(), &keeper, &keeper_tokens);
+        Self::notify_claim_hook(&env, vault_id, owner_tokens, &vault.owner);
 
         let mut fees: Map<Address, i128> = env
             .storage()
@@ -2022,6 +5040,14 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::KeeperFees, &fees);
+        let total_fees: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalKeeperFees)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalKeeperFees, &(total_fees + keeper_fee));
 
         env.events().publish(
             (Symbol::new(&env, "KeeperClaim"), vault_id),
@@ -2039,25 +5065,16 @@ impl VestingContract {
         fees.get(keeper).unwrap_or(0)
     }
 
-    // Rescue tokens accidentally sent directly to the contract address.
-    // Calculates unallocated_balance = contract_token_balance - total_vault_liabilities
-    // and transfers it to the admin.
-    pub fn rescue_unallocated_tokens(env: Env, token_address: Address) -> i128 {
-        Self::require_admin(&env);
-
-        if !Self::is_token_whitelisted(&env, &token_address) {
-            panic!("Token is not whitelisted");
-        }
-
-        let token_client = token::Client::new(&env, &token_address);
-        let contract_balance: i128 = token_client.balance(&env.current_contract_address());
-
-        if let Some(main_token) = env.storage().instance().get::<_, Address>(&DataKey::Token) {
-            if main_token == token_address {
-                panic!("Cannot rescue yield-bearing token. Yield is distributed to beneficiaries on claim.");
-            }
-        }
+    // Aggregate keeper fees paid across all keepers, kept as a running total
+    pub fn get_total_keeper_fees(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalKeeperFees)
+            .unwrap_or(0)
+    }
 
+    // Sum of unreleased amounts across all vaults
+    fn total_liabilities(env: &Env) -> i128 {
         let vault_count: u64 = env
             .storage()
             .instance()
@@ -2077,7 +5094,79 @@ impl VestingContract {
                 }
             }
         }
+        total_liabilities
+    }
+
+    // Returns (token balance held by the contract, outstanding vault liability).
+    // Balance below liability means the contract is underfunded.
+    pub fn get_solvency(env: Env) -> (i128, i128) {
+        let balance = Self::get_token_client(&env).balance(&env.current_contract_address());
+        (balance, Self::total_liabilities(&env))
+    }
+
+    // Admin-only: send the main token held above AdminBalance + all unreleased vault amounts
+    // (e.g. an accidental overfund) to the admin. Refused while anything is staked, since
+    // staked tokens are off-contract and the surplus can't be proven.
+    pub fn rescue_yield_surplus(env: Env) -> i128 {
+        Self::require_admin(&env);
+
+        let total_staked: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalStaked)
+            .unwrap_or(0);
+        if total_staked > 0 {
+            panic!("Cannot rescue surplus while tokens are staked");
+        }
+
+        let token_client = Self::get_token_client(&env);
+        let contract_balance = token_client.balance(&env.current_contract_address());
+        let admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+
+        let surplus = contract_balance - admin_balance - Self::total_liabilities(&env);
+        if surplus <= 0 {
+            panic!("No surplus to rescue");
+        }
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminAddress)
+            .unwrap_or_else(|| panic!("Admin not set"));
+        token_client.transfer(&env.current_contract_address(), &admin, &surplus);
+
+        env.events().publish(
+            (Symbol::new(&env, "YieldSurplusRescued"),),
+            (surplus, env.ledger().timestamp()),
+        );
+
+        surplus
+    }
+
+    // Rescue tokens accidentally sent directly to the contract address.
+    // Calculates unallocated_balance = contract_token_balance - total_vault_liabilities
+    // and transfers it to the admin.
+    pub fn rescue_unallocated_tokens(env: Env, token_address: Address) -> i128 {
+        Self::require_admin(&env);
+
+        if !Self::is_token_whitelisted(&env, &token_address) {
+            panic!("Token is not whitelisted");
+        }
+
+        let token_client = token::Client::new(&env, &token_address);
+        let contract_balance: i128 = token_client.balance(&env.current_contract_address());
+
+        if let Some(main_token) = env.storage().instance().get::<_, Address>(&DataKey::Token) {
+            if main_token == token_address {
+                panic!("Cannot rescue yield-bearing token. Yield is distributed to beneficiaries on claim.");
+            }
+        }
 
+        let total_liabilities = Self::total_liabilities(&env);
         let unallocated_balance = contract_balance - total_liabilities;
 
         if unallocated_balance <= 0 {
@@ -2105,4 +5194,5 @@ impl VestingContract {
     }
 }
 
-// mod test; // Disabled - tests need refactoring
+#[cfg(test)]
+mod test;
@@ -1,12 +1,13 @@
 #[cfg(test)]
 mod tests {
         use crate::{
-        BatchCreateData, Milestone, VestingContract, VestingContractClient,
+        BatchCreateData, Milestone, VestingContract, VestingContractClient, DELEGATE_CLAIM,
+        DELEGATE_STAKE, MAX_DURATION, MAX_MILESTONES, MAX_PROTOCOL_FEE_BPS,
     };
     use soroban_sdk::{
         contract, contractimpl,
         testutils::{Address as _, Ledger},
-        token, vec, Address, Env, Symbol,
+        token, vec, Address, Env, Map, Symbol, Vec,
     };
 
     // -------------------------------------------------------------------------
@@ -19,7 +20,7 @@ mod tests {
         let contract_id = env.register(VestingContract, ());
         let client = VestingContractClient::new(&env, &contract_id);
         let admin = Address::generate(&env);
-        client.initialize(&admin, &1_000_000i128);
+        client.initialize(&admin, &1_000_000i128, &true, &false);
         (env, contract_id, client, admin)
     }
 
@@ -32,6 +33,16 @@ mod tests {
         token::StellarAssetClient::new(env, token_addr).mint(recipient, &amount);
     }
 
+    // Fresh contract with a token set and the full initial supply held by the contract,
+    // so claims transfer shares 1:1.
+    fn setup_with_token() -> (Env, Address, VestingContractClient<'static>, Address, Address) {
+        let (env, contract_id, client, admin) = setup();
+        let token_addr = register_token(&env, &admin);
+        client.set_token(&token_addr);
+        mint_to(&env, &token_addr, &contract_id, 1_000_000i128);
+        (env, contract_id, client, admin, token_addr)
+    }
+
     // -------------------------------------------------------------------------
     // Admin ownership transfer
     // -------------------------------------------------------------------------
@@ -137,7 +148,7 @@ mod tests {
 
     #[test]
     fn test_step_vesting_full_claim_at_end() {
-        let (env, _cid, client, _admin) = setup();
+        let (env, _cid, client, _admin, _token) = setup_with_token();
         let beneficiary = Address::generate(&env);
         let start = 1_000u64;
         let end   = start + 101u64;
@@ -150,7 +161,7 @@ mod tests {
         );
 
         env.ledger().with_mut(|l| l.timestamp = end + 1);
-        let claimed = client.claim_tokens(&vault_id, &total);
+        let claimed = client.claim_tokens(&vault_id, &total, &None);
         assert_eq!(claimed, total);
 
         let vault = client.get_vault(&vault_id);
@@ -159,7 +170,7 @@ mod tests {
 
     #[test]
     fn test_lockup_only_claim_succeeds_at_end() {
-        let (env, _cid, client, _admin) = setup();
+        let (env, _cid, client, _admin, _token) = setup_with_token();
         let beneficiary = Address::generate(&env);
         let now      = env.ledger().timestamp();
         let duration = 1_000u64;
@@ -171,7 +182,7 @@ mod tests {
         );
 
         env.ledger().with_mut(|l| l.timestamp = now + duration);
-        let claimed = client.claim_tokens(&vault_id, &total);
+        let claimed = client.claim_tokens(&vault_id, &total, &None);
         assert_eq!(claimed, total);
     }
 
@@ -189,21 +200,22 @@ mod tests {
         );
 
         env.ledger().with_mut(|l| l.timestamp = now + duration - 1);
-        client.claim_tokens(&vault_id, &1i128);
+        client.claim_tokens(&vault_id, &1i128, &None);
     }
     
     #[test]
     fn test_admin_access_control() {
-        let (env, contract_id, client, admin) = setup();
+        let (env, _contract_id, client, admin) = setup();
         let new_admin = Address::generate(&env);
     
         // Test: Admin can propose new admin
         client.propose_new_admin(&new_admin);
+        assert_eq!(env.auths()[0].0, admin);
         assert_eq!(client.get_proposed_admin(), Some(new_admin.clone()));
     
         // Test: Proposed admin can accept ownership
-        new_admin.require_auth();
         client.accept_ownership();
+        assert_eq!(env.auths()[0].0, new_admin);
     
         // Verify admin transfer completed
         assert_eq!(client.get_admin(), new_admin);
@@ -221,16 +233,17 @@ fn test_periodic_vesting_monthly_steps() {
     let beneficiary = Address::generate(&env);
     
     // Initialize contract
-    let initial_supply = 1000000i128;
+    let initial_supply = 2000000i128; // covers the 1,200,000 vault below
     env.mock_all_auths();
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
     
     // Create vault with monthly vesting (30 days = 2,592,000 seconds)
     let amount = 1200000i128; // 1,200,000 tokens over 12 months = 100,000 per month
     let start_time = 1000000u64;
-    let end_time = start_time + (365 * 24 * 60 * 60); // 1 year
     let step_duration = 30 * 24 * 60 * 60; // 30 days in seconds
+    // Vesting is proportional to elapsed/duration, so the schedule spans exactly 12 steps
+    let end_time = start_time + 12 * step_duration;
     let keeper_fee = 1000i128;
     
     let vault_id = client.create_vault_full(
@@ -294,15 +307,16 @@ fn test_periodic_vesting_weekly_steps() {
     // Initialize contract
     let initial_supply = 1000000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
     
     // Set admin as caller
     
     // Create vault with weekly vesting (7 days = 604,800 seconds)
     let amount = 520000i128; // 520,000 tokens over 52 weeks = 10,000 per week
     let start_time = 1000000u64;
-    let end_time = start_time + (365 * 24 * 60 * 60); // 1 year
     let step_duration = 7 * 24 * 60 * 60; // 7 days in seconds
+    // Vesting is proportional to elapsed/duration, so the schedule spans exactly 52 steps
+    let end_time = start_time + 52 * step_duration;
     let keeper_fee = 100i128;
     
     let vault_id = client.create_vault_full(
@@ -339,16 +353,16 @@ fn test_linear_vesting_step_duration_zero() {
     let beneficiary = Address::generate(&env);
     
     // Initialize contract
-    let initial_supply = 1000000i128;
+    let initial_supply = 2000000i128; // covers the 1,200,000 vault below
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
     
     // Set admin as caller
     
     // Create vault with linear vesting (step_duration = 0)
     let amount = 1200000i128;
     let start_time = 1000000u64;
-    let end_time = start_time + (365 * 24 * 60 * 60); // 1 year
+    let end_time = start_time + (364 * 24 * 60 * 60); // 364 days, so 182 and 91 days are exact fractions
     let step_duration = 0u64; // Linear vesting
     let keeper_fee = 1000i128;
     
@@ -389,17 +403,21 @@ fn test_periodic_vesting_claim_partial() {
     // Initialize contract
     let initial_supply = 1000000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
+    let token_addr = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    client.set_token(&token_addr);
     
     // Set beneficiary as caller for claiming
     
     // Create vault with monthly vesting
     let amount = 120000i128; // 120,000 tokens over 12 months = 10,000 per month
     let start_time = 1000000u64;
-    let end_time = start_time + (365 * 24 * 60 * 60); // 1 year
     let step_duration = 30 * 24 * 60 * 60; // 30 days
+    let end_time = start_time + 12 * step_duration; // 12 monthly steps
     let keeper_fee = 100i128;
     
+    token::StellarAssetClient::new(&env, &token_addr).mint(&contract_id, &amount);
+
     let vault_id = client.create_vault_full(
         &beneficiary,
         &amount,
@@ -416,7 +434,7 @@ fn test_periodic_vesting_claim_partial() {
     
     // Claim partial amount
     let claim_amount = 15000i128; // Less than the 30,000 available
-    let claimed = client.claim_tokens(&vault_id, &claim_amount);
+    let claimed = client.claim_tokens(&vault_id, &claim_amount, &None);
     assert_eq!(claimed, claim_amount, "Should claim the requested amount");
     
     // Check remaining claimable
@@ -424,7 +442,7 @@ fn test_periodic_vesting_claim_partial() {
     assert_eq!(remaining_claimable, 15000i128, "Should have 15,000 tokens remaining claimable");
     
     // Claim the rest
-    let final_claim = client.claim_tokens(&vault_id, &remaining_claimable);
+    let final_claim = client.claim_tokens(&vault_id, &remaining_claimable, &None);
     assert_eq!(final_claim, remaining_claimable, "Should claim remaining tokens");
     
     // Check no more tokens available
@@ -445,7 +463,7 @@ fn test_vault_creation_access_control() {
     // Initialize contract with admin
     let initial_supply = 1000000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
     
     // Test: Admin can create vaults
     let vault_id = client.create_vault_full(
@@ -476,7 +494,7 @@ fn test_batch_operations_admin_control() {
     // Initialize contract with admin
     let initial_supply = 1000000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
     
     // Create batch data
     let batch_data = BatchCreateData {
@@ -487,7 +505,14 @@ fn test_batch_operations_admin_control() {
         keeper_fees: vec![&env, 0i128, 0i128],
         step_durations: vec![&env, 0u64, 0u64],
     };
-    
+
+    let vault_ids = client.batch_create_vaults_full(&batch_data);
+    assert_eq!(vault_ids.len(), 2);
+    // Only the admin authorized the batch
+    assert_eq!(env.auths()[0].0, admin);
+    assert_ne!(env.auths()[0].0, unauthorized_user);
+}
+
 #[test]
 fn test_batch_vault_creation() {
     let env = Env::default();
@@ -497,7 +522,7 @@ fn test_batch_vault_creation() {
     let admin = Address::generate(&env);
     let initial_supply = 1000000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
     
     let recipient1 = Address::generate(&env);
     let recipient2 = Address::generate(&env);
@@ -527,7 +552,7 @@ fn test_milestone_unlocking_and_claim_limits() {
     let admin = Address::generate(&env);
     let initial_supply = 1000000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
 }
 
 #[test]
@@ -541,7 +566,9 @@ fn test_step_vesting_fuzz() {
     
     let initial_supply = 1_000_000_000_000i128;
     env.mock_all_auths();
-    client.initialize(&admin, &initial_supply);
+    client.initialize(&admin, &initial_supply, &true, &false);
+    let token_addr = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    client.set_token(&token_addr);
 
     // Fuzz testing with prime numbers to check for truncation errors
     // Primes: 1009 (amount), 17 (step), 101 (duration)
@@ -562,13 +589,15 @@ fn test_step_vesting_fuzz() {
         &step_duration,
     );
 
+    token::StellarAssetClient::new(&env, &token_addr).mint(&contract_id, &total_amount);
+
     // Advance time to end
     env.ledger().with_mut(|li| {
         li.timestamp = end_time + 1;
     });
 
     // Claim all
-    let claimed = client.claim_tokens(&vault_id, &total_amount);
+    let claimed = client.claim_tokens(&vault_id, &total_amount, &None);
     
     // Assert full amount is claimed
     assert_eq!(claimed, total_amount);
@@ -583,10 +612,72 @@ fn test_step_vesting_fuzz() {
 pub struct MockStakingContract;
 
 #[contractimpl]
+#[allow(deprecated)]
 impl MockStakingContract {
     pub fn stake(env: Env, vault_id: u64, amount: i128, _validator: Address) {
         env.events().publish((Symbol::new(&env, "stake"), vault_id), amount);
     }
+
+    pub fn unstake(env: Env, vault_id: u64, amount: i128) {
+        env.events().publish((Symbol::new(&env, "unstake"), vault_id), amount);
+    }
+
+    pub fn unstake_from(env: Env, vault_id: u64, amount: i128, validator: Address) {
+        env.events()
+            .publish((Symbol::new(&env, "unstake_from"), vault_id), (amount, validator));
+    }
+
+    // Configure the reward paid to `recipient` on the next claim_rewards call
+    pub fn set_reward(env: Env, token: Address, recipient: Address, amount: i128) {
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "reward"), &(token, recipient, amount));
+    }
+
+    pub fn claim_rewards(env: Env, _vault_id: u64, _validator: Address) -> i128 {
+        let reward: Option<(Address, Address, i128)> =
+            env.storage().instance().get(&Symbol::new(&env, "reward"));
+        match reward {
+            Some((token, recipient, amount)) => {
+                token::Client::new(&env, &token).transfer(
+                    &env.current_contract_address(),
+                    &recipient,
+                    &amount,
+                );
+                env.storage().instance().remove(&Symbol::new(&env, "reward"));
+                amount
+            }
+            None => 0,
+        }
+    }
+}
+
+// Mock claim hook that records the last callback it received
+#[contract]
+pub struct MockClaimHook;
+
+#[contractimpl]
+impl MockClaimHook {
+    pub fn on_claim(env: Env, vault_id: u64, amount: i128, owner: Address) {
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "last"), &(vault_id, amount, owner));
+    }
+
+    pub fn last_claim(env: Env) -> Option<(u64, i128, Address)> {
+        env.storage().instance().get(&Symbol::new(&env, "last"))
+    }
+}
+
+// Mock claim hook that always fails
+#[contract]
+pub struct FailingClaimHook;
+
+#[contractimpl]
+impl FailingClaimHook {
+    pub fn on_claim(_env: Env, _vault_id: u64, _amount: i128, _owner: Address) {
+        panic!("hook failed");
+    }
 }
 
     // -------------------------------------------------------------------------
@@ -667,7 +758,7 @@ impl MockStakingContract {
 
     #[test]
     fn test_milestone_unlock_and_claim() {
-        let (env, _cid, client, _admin) = setup();
+        let (env, _cid, client, _admin, _token) = setup_with_token();
         let beneficiary = Address::generate(&env);
         let now = env.ledger().timestamp();
 
@@ -684,11 +775,11 @@ impl MockStakingContract {
         client.set_milestones(&vault_id, &milestones);
 
         client.unlock_milestone(&vault_id, &1u64);
-        let claimed = client.claim_tokens(&vault_id, &500i128);
+        let claimed = client.claim_tokens(&vault_id, &500i128, &None);
         assert_eq!(claimed, 500i128);
 
         client.unlock_milestone(&vault_id, &2u64);
-        let claimed2 = client.claim_tokens(&vault_id, &500i128);
+        let claimed2 = client.claim_tokens(&vault_id, &500i128, &None);
         assert_eq!(claimed2, 500i128);
     }
 
@@ -709,7 +800,7 @@ impl MockStakingContract {
             Milestone { id: 1, percentage: 100, is_unlocked: false },
         ];
         client.set_milestones(&vault_id, &milestones);
-        client.claim_tokens(&vault_id, &1i128);
+        client.claim_tokens(&vault_id, &1i128, &None);
     }
 
     // -------------------------------------------------------------------------
@@ -741,7 +832,7 @@ impl MockStakingContract {
 
     #[test]
     fn test_invariant_holds_after_operations() {
-        let (env, _cid, client, _admin) = setup();
+        let (env, _cid, client, _admin, _token) = setup_with_token();
         let beneficiary = Address::generate(&env);
         let now = env.ledger().timestamp();
         let initial_supply = 1_000_000i128;
@@ -758,7 +849,7 @@ impl MockStakingContract {
 
         // After partial claim: 5_000 paid out, 5_000 still locked
         env.ledger().with_mut(|l| l.timestamp = now + 500);
-        client.claim_tokens(&vault_id, &5_000i128);
+        client.claim_tokens(&vault_id, &5_000i128, &None);
         assert!(client.check_invariant(), "invariant failed after partial claim");
         let (locked2, _claimed2, admin_bal2) = client.get_contract_state();
         // 5_000 was paid out to beneficiary, so locked + admin = initial - 5_000
@@ -822,6 +913,8 @@ impl MockStakingContract {
     #[test]
     fn test_rescue_after_partial_claim_adjusts_liability() {
         let (env, contract_id, client, admin) = setup();
+        let vault_token = register_token(&env, &admin);
+        client.set_token(&vault_token);
         let token_addr = register_token(&env, &admin);
         client.add_to_whitelist(&token_addr);
 
@@ -833,9 +926,10 @@ impl MockStakingContract {
             &0i128, &true, &false, &0u64,
         );
 
-        // Claim 1000 → remaining liability 3000
+        // Claim 1000 (funded up front) → remaining liability 3000
+        mint_to(&env, &vault_token, &contract_id, 1_000i128);
         env.ledger().with_mut(|l| l.timestamp = now + 1_001);
-        client.claim_tokens(&vault_id, &1_000i128);
+        client.claim_tokens(&vault_id, &1_000i128, &None);
 
         mint_to(&env, &token_addr, &contract_id, 5_000i128);
 
@@ -878,6 +972,8 @@ impl MockStakingContract {
     #[test]
     fn test_rescue_after_full_claim_all_tokens_rescuable() {
         let (env, contract_id, client, admin) = setup();
+        let vault_token = register_token(&env, &admin);
+        client.set_token(&vault_token);
         let token_addr = register_token(&env, &admin);
         client.add_to_whitelist(&token_addr);
 
@@ -889,9 +985,10 @@ impl MockStakingContract {
             &0i128, &true, &false, &0u64,
         );
 
-        // Claim everything → liability = 0
+        // Claim everything (funded up front) → liability = 0
+        mint_to(&env, &vault_token, &contract_id, 2_000i128);
         env.ledger().with_mut(|l| l.timestamp = now + 1_001);
-        client.claim_tokens(&vault_id, &2_000i128);
+        client.claim_tokens(&vault_id, &2_000i128, &None);
 
         // Stray deposit after full claim
         mint_to(&env, &token_addr, &contract_id, 500i128);
@@ -990,6 +1087,3810 @@ impl MockStakingContract {
 
         client.rescue_unallocated_tokens(&token_addr); // must panic
     }
-}
-}
 
+    // -------------------------------------------------------------------------
+    // Batch delegates
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_batch_set_delegates_owner_authorized() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let owner1    = Address::generate(&env);
+        let owner2    = Address::generate(&env);
+        let delegate1 = Address::generate(&env);
+        let delegate2 = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let v1 = client.create_vault_full(
+            &owner1, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &owner2, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        let mut assignments = Map::new(&env);
+        assignments.set(v1, delegate1.clone());
+        assignments.set(v2, delegate2.clone());
+        client.batch_set_delegates(&assignments, &false);
+
+        assert_eq!(client.get_vault(&v1).delegate, Some(delegate1));
+        assert_eq!(client.get_vault(&v2).delegate, Some(delegate2));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.claim_as_delegate(&v1, &1_000i128), 1_000i128);
+        assert_eq!(client.claim_as_delegate(&v2, &1_000i128), 1_000i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Reporting
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_average_vested_over_linear_window() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        // Samples at 0, 100, ..., 1000 → mean equals the endpoint average (50%)
+        let avg = client.average_vested_over(&vault_id, &now, &(now + 1_000), &11u32);
+        assert_eq!(avg, 500i128);
+    }
+
+    #[test]
+    fn test_average_vested_over_full_time_range() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        // Samples at the start, the midpoint and u64::MAX: 0, 1_000, 1_000
+        let avg = client.average_vested_over(&vault_id, &now, &u64::MAX, &3u32);
+        assert_eq!(avg, 666i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid sample count")]
+    fn test_average_vested_over_rejects_too_many_samples() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.average_vested_over(&vault_id, &now, &(now + 1_000), &1_000u32);
+    }
+
+    // -------------------------------------------------------------------------
+    // Creation seal
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Vault creation is sealed")]
+    fn test_seal_creation_blocks_create() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.seal_creation();
+        assert!(client.is_creation_sealed());
+
+        client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+    }
+
+    #[test]
+    fn test_seal_creation_keeps_claims_live() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.seal_creation();
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.claim_tokens(&vault_id, &1_000i128, &None), 1_000i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Freeze history
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_ever_frozen_survives_unfreeze() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert!(!client.was_ever_frozen(&vault_id));
+
+        client.freeze_vault(&vault_id);
+        client.unfreeze_vault(&vault_id);
+
+        assert!(!client.is_vault_frozen(&vault_id));
+        assert!(client.was_ever_frozen(&vault_id));
+        assert!(client.get_vault(&vault_id).ever_frozen);
+    }
+
+    // -------------------------------------------------------------------------
+    // Basis-point progress views
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_vested_and_claimed_bps_linear() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        assert_eq!(client.get_vested_bps(&vault_id), 0);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 250);
+        assert_eq!(client.get_vested_bps(&vault_id), 2_500);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        assert_eq!(client.get_vested_bps(&vault_id), 5_000);
+        client.claim_tokens(&vault_id, &500i128, &None);
+        assert_eq!(client.get_claimed_bps(&vault_id), 5_000);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.get_vested_bps(&vault_id), 10_000);
+    }
+
+    // -------------------------------------------------------------------------
+    // Step offset
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_step_offset_shifts_first_unlock() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let start  = 1_000u64;
+        let step   = 100u64;
+        let offset = 40u64;
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &start, &(start + 1_000),
+            &0i128, &true, &false, &step,
+        );
+        client.set_step_offset(&vault_id, &offset);
+
+        // Without the offset the first step would unlock here
+        env.ledger().with_mut(|l| l.timestamp = start + step);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0);
+
+        // First unlock lands `offset` seconds later
+        env.ledger().with_mut(|l| l.timestamp = start + step + offset);
+        assert_eq!(client.get_claimable_amount(&vault_id), 100i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Step offset must be less than step duration")]
+    fn test_step_offset_must_be_below_step_duration() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &1_000u64, &2_000u64,
+            &0i128, &true, &false, &100u64,
+        );
+        client.set_step_offset(&vault_id, &100u64);
+    }
+
+    // -------------------------------------------------------------------------
+    // Burn on revoke
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_revoke_burns_when_enabled() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary  = Address::generate(&env);
+        let burn_address = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &3_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_burn_on_revoke(&true, &burn_address);
+
+        let (_, _, admin_before) = client.get_contract_state();
+        let returned = client.revoke_tokens(&vault_id);
+        let (_, _, admin_after) = client.get_contract_state();
+
+        assert_eq!(returned, 3_000i128);
+        assert_eq!(admin_after, admin_before);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&burn_address), 3_000i128);
+    }
+
+    #[test]
+    fn test_revoke_partial_burns_when_enabled() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary  = Address::generate(&env);
+        let burn_address = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &3_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_burn_on_revoke(&true, &burn_address);
+
+        let (_, _, admin_before) = client.get_contract_state();
+        client.revoke_partial(&vault_id, &1_000i128);
+        let (_, _, admin_after) = client.get_contract_state();
+
+        assert_eq!(admin_after, admin_before);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&burn_address), 1_000i128);
+    }
+
+    #[test]
+    fn test_batch_revoke_burns_when_enabled() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let burn_address = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let first = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let second = client.create_vault_full(
+            &Address::generate(&env), &2_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_burn_on_revoke(&true, &burn_address);
+
+        let (_, _, admin_before) = client.get_contract_state();
+        let returned = client.batch_revoke(&vec![&env, first, second]);
+        let (_, _, admin_after) = client.get_contract_state();
+
+        assert_eq!(returned, 3_000i128);
+        assert_eq!(admin_after, admin_before);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&burn_address), 3_000i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Claimable projection
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claimable_at_projects_future_amounts() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let start = 1_000u64;
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &start, &(start + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        // Ledger is still before start_time; projections don't depend on it
+        assert_eq!(client.claimable_at(&vault_id, &(start - 1)), 0);
+        assert_eq!(client.claimable_at(&vault_id, &(start + 500)), 500i128);
+        assert_eq!(client.claimable_at(&vault_id, &(start + 1_000)), 1_000i128);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0);
+    }
+
+    // -------------------------------------------------------------------------
+    // Rounding
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_rounding_never_strands_more_than_one_unit() {
+        let (env, _cid, client, _admin) = setup();
+        let start = 1_000u64;
+
+        // Prime amounts and durations maximise truncation error
+        for (total, duration) in [(1_009i128, 101u64), (7_919i128, 97u64), (104_729i128, 613u64)] {
+            let beneficiary = Address::generate(&env);
+            let vault_id = client.create_vault_full(
+                &beneficiary, &total, &start, &(start + duration),
+                &0i128, &true, &false, &0u64,
+            );
+
+            for elapsed in 0..=duration {
+                let vested = client.claimable_at(&vault_id, &(start + elapsed));
+                let exact_floor = total * elapsed as i128 / duration as i128;
+                assert!(
+                    vested >= exact_floor && vested - exact_floor <= 1,
+                    "vested {vested} drifts from exact {exact_floor} at elapsed {elapsed}"
+                );
+            }
+            assert_eq!(client.claimable_at(&vault_id, &(start + duration)), total);
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Timed freeze
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_freeze_until_lifts_automatically() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.freeze_vault_until(&vault_id, &(now + 2_000));
+        assert_eq!(client.get_freeze_expiry(&vault_id), now + 2_000);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_999);
+        assert!(client.is_vault_frozen(&vault_id));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        assert!(!client.is_vault_frozen(&vault_id));
+        assert_eq!(client.claim_tokens(&vault_id, &1_000i128, &None), 1_000i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault is frozen - claims are disabled")]
+    fn test_freeze_until_blocks_claims_before_expiry() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.freeze_vault_until(&vault_id, &(now + 2_000));
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&vault_id, &1_000i128, &None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Contract metadata
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_version_and_contract_type() {
+        let (env, _cid, client, _admin) = setup();
+        assert_eq!(client.contract_type(), Symbol::new(&env, "vesting"));
+        assert_eq!(client.version(), (1, 0, 0));
+    }
+
+    // -------------------------------------------------------------------------
+    // Merge vaults
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_merge_compatible_vaults() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let v1 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &beneficiary, &3_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&v1, &200i128, &None);
+
+        let merged_id = client.merge_vaults(&vec![&env, v1, v2]);
+        let merged = client.get_vault(&merged_id);
+        assert_eq!(merged.owner, beneficiary);
+        assert_eq!(merged.total_amount, 4_000i128);
+        assert_eq!(merged.released_amount, 200i128);
+
+        let old = client.get_vault(&v1);
+        assert_eq!(old.released_amount, old.total_amount);
+        assert_eq!(client.get_user_vaults(&beneficiary), vec![&env, merged_id]);
+        assert_eq!(client.get_claimable_amount(&merged_id), 1_800i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vaults are not compatible for merging")]
+    fn test_merge_rejects_different_schedules() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let v1 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 2_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.merge_vaults(&vec![&env, v1, v2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot merge frozen vault")]
+    fn test_merge_rejects_frozen_vault() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let v1 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.freeze_vault(&v2);
+
+        client.merge_vaults(&vec![&env, v1, v2]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Vault splitting
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_split_vault_preserves_totals() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let friend = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &4_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 250);
+        client.claim_tokens(&vault_id, &1_000i128, &None);
+
+        let split_id = client.split_vault(&vault_id, &1_000i128, &friend);
+        let original = client.get_vault(&vault_id);
+        let split = client.get_vault(&split_id);
+
+        assert_eq!(original.total_amount + split.total_amount, 4_000i128);
+        assert_eq!(original.released_amount + split.released_amount, 1_000i128);
+        assert_eq!(split.total_amount, 1_000i128);
+        assert_eq!(split.released_amount, 250i128);
+        assert_eq!(split.owner, friend);
+        assert_eq!(split.start_time, original.start_time);
+        assert_eq!(split.end_time, original.end_time);
+        assert_eq!(client.get_user_vaults(&friend), vec![&env, split_id]);
+        assert_eq!(client.get_user_vaults(&beneficiary), vec![&env, vault_id]);
+
+        // Both halves keep vesting on the original schedule.
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        assert_eq!(client.get_claimable_amount(&vault_id), 750i128);
+        assert_eq!(client.get_claimable_amount(&split_id), 250i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault is non-transferable")]
+    fn test_split_vault_requires_transferable() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let friend = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.split_vault(&vault_id, &500i128, &friend);
+    }
+
+    #[test]
+    fn test_split_vault_inherits_claim_deadline() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+
+        let split_id = client.split_vault(&vault_id, &400i128, &Address::generate(&env));
+        assert_eq!(client.get_claim_deadline(&split_id), now + 2_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim deadline passed")]
+    fn test_split_vault_rejected_after_claim_deadline() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        client.split_vault(&vault_id, &400i128, &Address::generate(&env));
+    }
+
+    #[test]
+    #[should_panic(expected = "New owner must differ from current owner")]
+    fn test_split_vault_to_current_owner_rejected() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.split_vault(&vault_id, &400i128, &beneficiary);
+    }
+
+    // -------------------------------------------------------------------------
+    // External contract views
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_token_and_staking_contract_getters() {
+        let (env, _cid, client, admin) = setup();
+        assert_eq!(client.get_token(), None);
+        assert_eq!(client.get_staking_contract(), None);
+
+        let token_addr = register_token(&env, &admin);
+        let staking = Address::generate(&env);
+        client.set_token(&token_addr);
+        client.set_staking_contract(&staking);
+
+        assert_eq!(client.get_token(), Some(token_addr));
+        assert_eq!(client.get_staking_contract(), Some(staking));
+    }
+
+    // -------------------------------------------------------------------------
+    // Stake limits
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_stake_within_limits() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        client.set_max_stake_bps(&5_000u32);
+        client.approve_validator(&validator);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.stake_tokens(&vault_id, &500i128, &validator);
+        assert_eq!(client.get_vault(&vault_id).staked_amount, 500i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stake exceeds max stake bps")]
+    fn test_stake_above_bps_cap_panics() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        client.set_max_stake_bps(&5_000u32);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.stake_tokens(&vault_id, &501i128, &validator);
+    }
+
+    #[test]
+    #[should_panic(expected = "Validator not approved")]
+    fn test_stake_to_unapproved_validator_panics() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        client.approve_validator(&Address::generate(&env));
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.stake_tokens(&vault_id, &100i128, &Address::generate(&env));
+    }
+
+    // -------------------------------------------------------------------------
+    // Claim max
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_max_claims_all_vested() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 400);
+        assert_eq!(client.claim_max(&vault_id), 400i128);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0i128);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&beneficiary), 400i128);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.claim_max(&vault_id), 600i128);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 1_000i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "No tokens available to claim")]
+    fn test_claim_max_with_nothing_vested_panics() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &(now + 100), &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.claim_max(&vault_id);
+    }
+
+    // -------------------------------------------------------------------------
+    // Yield-bearing toggle
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_non_yield_token_claims_one_to_one() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(VestingContract, ());
+        let client = VestingContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        client.initialize(&admin, &1_000_000i128, &false, &false);
+        assert!(!client.is_yield_bearing());
+
+        // Hold more than the supply so share-scaling would overpay.
+        let token_addr = register_token(&env, &admin);
+        client.set_token(&token_addr);
+        mint_to(&env, &token_addr, &contract_id, 1_500_000i128);
+
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 400);
+        client.claim_tokens(&vault_id, &400i128, &None);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&beneficiary), 400i128);
+    }
+
+    #[test]
+    fn test_yield_bearing_is_default_behavior() {
+        let (_env, _cid, client, _admin) = setup();
+        assert!(client.is_yield_bearing());
+    }
+
+    // -------------------------------------------------------------------------
+    // Yield harvest
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_harvest_reports_surplus() {
+        let (env, cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.create_vault_full(
+            &beneficiary, &10_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_accrued_yield(), 0i128);
+        assert_eq!(client.harvest(), 0i128);
+
+        mint_to(&env, &token_addr, &cid, 2_500i128);
+        assert_eq!(client.harvest(), 2_500i128);
+        assert_eq!(client.get_accrued_yield(), 2_500i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Self freeze
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_owner_self_freeze_and_admin_unfreeze() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.self_freeze_vault(&vault_id);
+        assert!(client.is_vault_frozen(&vault_id));
+        assert!(client.was_ever_frozen(&vault_id));
+
+        client.unfreeze_vault(&vault_id);
+        assert!(!client.is_vault_frozen(&vault_id));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.claim_tokens(&vault_id, &1_000i128, &None), 1_000i128);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_owner_cannot_self_unfreeze() {
+        use soroban_sdk::testutils::{MockAuth, MockAuthInvoke};
+        use soroban_sdk::IntoVal;
+
+        let (env, cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.self_freeze_vault(&vault_id);
+
+        // Only the beneficiary signs; unfreeze requires the admin.
+        client
+            .mock_auths(&[MockAuth {
+                address: &beneficiary,
+                invoke: &MockAuthInvoke {
+                    contract: &cid,
+                    fn_name: "unfreeze_vault",
+                    args: (vault_id,).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }])
+            .unfreeze_vault(&vault_id);
+    }
+
+    // -------------------------------------------------------------------------
+    // Vault summary
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_vault_summary_matches_full_vault() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 600);
+        client.claim_tokens(&vault_id, &200i128, &None);
+        client.freeze_vault(&vault_id);
+
+        let (owner, total, released, claimable, is_frozen) = client.get_vault_summary(&vault_id);
+        let vault = client.get_vault(&vault_id);
+        assert_eq!(owner, vault.owner);
+        assert_eq!(total, vault.total_amount);
+        assert_eq!(released, vault.released_amount);
+        assert_eq!(claimable, client.get_claimable_amount(&vault_id));
+        assert_eq!(claimable, 400i128);
+        assert!(is_frozen);
+    }
+
+    // -------------------------------------------------------------------------
+    // Beneficiary allowlist
+    // -------------------------------------------------------------------------
+
+    fn setup_kyc() -> (Env, VestingContractClient<'static>) {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(VestingContract, ());
+        let client = VestingContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        client.initialize(&admin, &1_000_000i128, &true, &true);
+        (env, client)
+    }
+
+    #[test]
+    #[should_panic(expected = "Beneficiary not on allowlist")]
+    fn test_kyc_rejects_unlisted_beneficiary() {
+        let (env, client) = setup_kyc();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Beneficiary not on allowlist")]
+    fn test_kyc_rejects_unlisted_batch_recipient() {
+        let (env, client) = setup_kyc();
+        let listed = Address::generate(&env);
+        let unlisted = Address::generate(&env);
+        client.add_allowed_beneficiary(&listed);
+
+        let batch = BatchCreateData {
+            recipients: vec![&env, listed, unlisted],
+            amounts: vec![&env, 100i128, 100i128],
+            start_times: vec![&env, 0u64, 0u64],
+            end_times: vec![&env, 1_000u64, 1_000u64],
+            keeper_fees: vec![&env, 0i128, 0i128],
+            step_durations: vec![&env, 0u64, 0u64],
+        };
+        client.batch_create_vaults_full(&batch);
+    }
+
+    #[test]
+    #[should_panic(expected = "Beneficiary not on allowlist")]
+    fn test_kyc_rejects_unlisted_split_recipient() {
+        let (env, client) = setup_kyc();
+        let beneficiary = Address::generate(&env);
+        client.add_allowed_beneficiary(&beneficiary);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.split_vault(&vault_id, &400i128, &Address::generate(&env));
+    }
+
+    #[test]
+    fn test_kyc_allows_listed_beneficiary() {
+        let (env, client) = setup_kyc();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.add_allowed_beneficiary(&beneficiary);
+        let vault_id = client.create_vault_lazy(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_vault(&vault_id).owner, beneficiary);
+
+        client.remove_allowed_beneficiary(&beneficiary);
+        assert_eq!(client.get_vault(&vault_id).owner, beneficiary);
+    }
+
+    #[test]
+    fn test_kyc_off_accepts_any_beneficiary() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_vault(&vault_id).owner, beneficiary);
+    }
+
+    // -------------------------------------------------------------------------
+    // Revoke to destination
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_revoke_to_sends_to_destination() {
+        let (env, contract_id, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let recovery = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let (_, _, admin_balance_before) = client.get_contract_state();
+
+        env.ledger().with_mut(|l| l.timestamp = now + 300);
+        client.claim_tokens(&vault_id, &300i128, &None);
+
+        assert_eq!(client.revoke_to(&vault_id, &recovery), 700i128);
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&recovery), 700i128);
+        assert_eq!(token_client.balance(&beneficiary), 300i128);
+        assert_eq!(token_client.balance(&contract_id), 999_000i128);
+        assert_eq!(client.get_revoke_destination(&vault_id), Some(recovery));
+
+        let (locked, _, admin_balance_after) = client.get_contract_state();
+        assert_eq!(locked, 0i128);
+        assert_eq!(admin_balance_after, admin_balance_before);
+    }
+
+    // -------------------------------------------------------------------------
+    // Delegate permissions
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_stake_only_delegate_can_stake() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(delegate.clone()), &DELEGATE_STAKE);
+
+        client.stake_as_delegate(&vault_id, &400i128, &validator);
+        let vault = client.get_vault(&vault_id);
+        assert_eq!(vault.staked_amount, 400i128);
+        assert_eq!(vault.delegate_permissions, DELEGATE_STAKE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delegate lacks permission")]
+    fn test_stake_only_delegate_cannot_claim() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(delegate), &DELEGATE_STAKE);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_as_delegate(&vault_id, &100i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delegate lacks permission")]
+    fn test_claim_only_delegate_cannot_stake() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(delegate), &DELEGATE_CLAIM);
+
+        client.stake_as_delegate(&vault_id, &100i128, &Address::generate(&env));
+    }
+
+    // -------------------------------------------------------------------------
+    // Lazy vault reconciliation
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_reconcile_lazy_vault_adds_to_user_vaults() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_lazy(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_user_vaults(&beneficiary).len(), 0);
+
+        assert!(client.reconcile_lazy_vault(&vault_id));
+        assert!(!client.reconcile_lazy_vault(&vault_id));
+        assert_eq!(client.get_user_vaults(&beneficiary), vec![&env, vault_id]);
+    }
+
+    #[test]
+    fn test_sweep_uninitialized_range() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let v1 = client.create_vault_lazy(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v3 = client.create_vault_lazy(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        assert_eq!(client.sweep_uninitialized(&1u64, &10u32), 2u32);
+        assert_eq!(client.get_user_vaults(&beneficiary), vec![&env, v2, v1, v3]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Fully vested time / remaining locked
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_fully_vested_at_aligns_to_final_step() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &250u64,
+        );
+        assert_eq!(client.get_fully_vested_at(&vault_id), now + 1_000);
+        assert_eq!(client.get_remaining_locked(&vault_id), 1_000i128);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 999);
+        assert_eq!(client.get_remaining_locked(&vault_id), 250i128);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.get_remaining_locked(&vault_id), 0i128);
+    }
+
+    #[test]
+    fn test_fully_vested_at_uneven_steps_is_end_time() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &300u64,
+        );
+        assert_eq!(client.get_fully_vested_at(&vault_id), now + 1_000);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 900);
+        assert_eq!(client.get_remaining_locked(&vault_id), 100i128);
+    }
+
+    #[test]
+    fn test_fully_vested_at_reached_by_rounding_before_end() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        // 3 units over 1_000s in 900s steps: 3 * 900 / 1_000 rounds up to 3.
+        let vault_id = client.create_vault_full(
+            &beneficiary, &3i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &900u64,
+        );
+        assert_eq!(client.get_fully_vested_at(&vault_id), now + 900);
+    }
+
+    // -------------------------------------------------------------------------
+    // Outflow throttle
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Outflow limit reached")]
+    fn test_outflow_limit_blocks_claims_within_window() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_outflow_limit(&500i128, &3_600u64);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&vault_id, &400i128, &None);
+        client.claim_tokens(&vault_id, &200i128, &None);
+    }
+
+    #[test]
+    fn test_outflow_limit_resets_after_window() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_outflow_limit(&500i128, &3_600u64);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&vault_id, &500i128, &None);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 3_600);
+        assert_eq!(client.claim_tokens(&vault_id, &500i128, &None), 500i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Staking events
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_stake_and_unstake_emit_events() {
+        use soroban_sdk::testutils::Events;
+        use soroban_sdk::IntoVal;
+
+        let (env, cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.stake_tokens(&vault_id, &800i128, &validator);
+        assert_eq!(
+            env.events().all().filter_by_contract(&cid),
+            vec![
+                &env,
+                (
+                    cid.clone(),
+                    (Symbol::new(&env, "TokensStaked"), vault_id).into_val(&env),
+                    (800i128, validator.clone()).into_val(&env),
+                ),
+            ]
+        );
+
+        // Claiming everything needs 800 back from the staking contract.
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&vault_id, &1_000i128, &None);
+        assert_eq!(
+            env.events().all().filter_by_contract(&cid),
+            vec![
+                &env,
+                (
+                    cid.clone(),
+                    (Symbol::new(&env, "TokensUnstaked"), vault_id).into_val(&env),
+                    800i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_vault(&vault_id).staked_amount, 0i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Keeper minimum payout
+    // -------------------------------------------------------------------------
+
+    // Contract whose token balance has halved relative to its shares, so share-denominated
+    // keeper fees pay out half as many tokens.
+    fn setup_half_backed_vault(keeper_fee: i128) -> (Env, VestingContractClient<'static>, u64) {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(VestingContract, ());
+        let client = VestingContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        client.initialize(&admin, &1_000i128, &true, &false);
+
+        let token_addr = register_token(&env, &admin);
+        client.set_token(&token_addr);
+        mint_to(&env, &token_addr, &contract_id, 500i128);
+
+        let now = env.ledger().timestamp();
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &keeper_fee, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        (env, client, vault_id)
+    }
+
+    #[test]
+    fn test_auto_claim_pays_keeper_at_minimum() {
+        let (env, client, vault_id) = setup_half_backed_vault(100i128);
+        let keeper = Address::generate(&env);
+
+        client.auto_claim(&vault_id, &keeper, &50i128);
+        assert_eq!(client.get_keeper_fee(&keeper), 100i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Keeper payout below minimum")]
+    fn test_auto_claim_reverts_below_keeper_minimum() {
+        let (env, client, vault_id) = setup_half_backed_vault(100i128);
+        let keeper = Address::generate(&env);
+
+        client.auto_claim(&vault_id, &keeper, &80i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Batch validation
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Batch arrays length mismatch")]
+    fn test_batch_create_rejects_mismatched_arrays() {
+        let (env, _cid, client, _admin) = setup();
+
+        let batch = BatchCreateData {
+            recipients: vec![&env, Address::generate(&env), Address::generate(&env)],
+            amounts: vec![&env, 100i128, 100i128],
+            start_times: vec![&env, 0u64, 0u64],
+            end_times: vec![&env, 1_000u64, 1_000u64],
+            keeper_fees: vec![&env, 0i128, 0i128],
+            step_durations: vec![&env, 0u64],
+        };
+        client.batch_create_vaults_full(&batch);
+    }
+
+    #[test]
+    #[should_panic(expected = "Batch arrays length mismatch")]
+    fn test_batch_create_lazy_rejects_mismatched_arrays() {
+        let (env, _cid, client, _admin) = setup();
+
+        let batch = BatchCreateData {
+            recipients: vec![&env, Address::generate(&env)],
+            amounts: vec![&env, 100i128, 100i128],
+            start_times: vec![&env, 0u64],
+            end_times: vec![&env, 1_000u64],
+            keeper_fees: vec![&env, 0i128],
+            step_durations: vec![&env, 0u64],
+        };
+        client.batch_create_vaults_lazy(&batch);
+    }
+
+    // -------------------------------------------------------------------------
+    // Per-call claim cap
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_at_cap_succeeds() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_claim_cap(&vault_id, &300i128);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.claim_tokens(&vault_id, &300i128, &None), 300i128);
+        assert_eq!(client.claim_max(&vault_id), 300i128);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 600i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Exceeds per-call claim cap")]
+    fn test_claim_above_cap_panics() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_claim_cap(&vault_id, &300i128);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&vault_id, &301i128, &None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Vault state export / import
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_export_import_vault_state_round_trip() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let v1 = client.create_vault_full(
+            &alice, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &bob, &2_000i128, &now, &(now + 2_000),
+            &0i128, &true, &true, &100u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&v1, &500i128, &None);
+
+        let exported = client.export_vault_state(&1u64, &10u32);
+        assert_eq!(exported.len(), 2);
+
+        let new_id = env.register(VestingContract, ());
+        let new_client = VestingContractClient::new(&env, &new_id);
+        new_client.initialize(&Address::generate(&env), &1_000_000i128, &true, &false);
+        new_client.import_vault_state(&exported);
+
+        for vault_id in [v1, v2] {
+            let (old, new) = (client.get_vault(&vault_id), new_client.get_vault(&vault_id));
+            assert_eq!(new.owner, old.owner);
+            assert_eq!(new.total_amount, old.total_amount);
+            assert_eq!(new.released_amount, old.released_amount);
+            assert_eq!((new.start_time, new.end_time), (old.start_time, old.end_time));
+            assert_eq!(new.is_transferable, old.is_transferable);
+            assert_eq!(new.step_duration, old.step_duration);
+            assert_eq!(
+                new_client.get_claimable_amount(&vault_id),
+                client.get_claimable_amount(&vault_id)
+            );
+        }
+        assert_eq!(new_client.get_user_vaults(&alice), vec![&env, v1]);
+        assert_eq!(new_client.get_user_vaults(&bob), vec![&env, v2]);
+        assert_eq!(new_client.get_contract_state(), (2_500i128, 500i128, 997_500i128));
+    }
+
+    #[test]
+    fn test_export_import_vault_state_carries_per_vault_storage() {
+        use crate::DataKey;
+
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let milestones = vec![
+            &env,
+            Milestone { id: 1, percentage: 50, is_unlocked: false },
+            Milestone { id: 2, percentage: 50, is_unlocked: false },
+        ];
+        client.set_milestones(&vault_id, &milestones);
+        client.unlock_milestone(&vault_id, &1u64);
+        client.set_claim_deadline(&vault_id, &(now + 5_000));
+        client.set_staking_contract(&env.register(MockStakingContract, ()));
+        client.stake_tokens(&vault_id, &300i128, &validator);
+
+        let exported = client.export_vault_state(&1u64, &10u32);
+        let new_id = env.register(VestingContract, ());
+        let new_client = VestingContractClient::new(&env, &new_id);
+        new_client.initialize(&Address::generate(&env), &1_000_000i128, &true, &false);
+        new_client.import_vault_state(&exported);
+
+        let imported = new_client.get_milestones(&vault_id);
+        assert_eq!(imported.len(), 2);
+        assert!(imported.get(0).unwrap().is_unlocked);
+        assert!(!imported.get(1).unwrap().is_unlocked);
+        assert_eq!(new_client.get_claim_deadline(&vault_id), now + 5_000);
+        assert_eq!(new_client.get_validator_stakes(&vault_id).get(validator), Some(300i128));
+        assert_eq!(
+            new_client.get_claimable_amount(&vault_id),
+            client.get_claimable_amount(&vault_id)
+        );
+        let total_staked: i128 = env.as_contract(&new_id, || {
+            env.storage().instance().get(&DataKey::TotalStaked).unwrap()
+        });
+        assert_eq!(total_staked, 300i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot import into a contract with existing vaults")]
+    fn test_import_vault_state_requires_empty_contract() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let exported = client.export_vault_state(&1u64, &10u32);
+        client.import_vault_state(&exported);
+    }
+
+    // -------------------------------------------------------------------------
+    // Token decimals
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_token_decimals_stored_at_set_token() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+
+        let decimals = token::Client::new(&env, &token_addr).decimals();
+        assert_eq!(client.get_token_decimals(), Some(decimals));
+        assert_eq!(client.to_display_amount(&(12_345 * 10i128.pow(decimals)), &decimals), 12_345);
+    }
+
+    #[test]
+    fn test_token_decimals_unset_before_set_token() {
+        let (_env, _cid, client, _admin) = setup();
+        assert_eq!(client.get_token_decimals(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Decimals too large")]
+    fn test_to_display_amount_rejects_oversized_decimals() {
+        let (_env, _cid, client, _admin) = setup();
+        client.to_display_amount(&1_000i128, &39u32);
+    }
+
+    // -------------------------------------------------------------------------
+    // Claim hook
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_hook_receives_callback() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let hook = env.register(MockClaimHook, ());
+        let hook_client = MockClaimHookClient::new(&env, &hook);
+        client.set_claim_hook(&Some(hook.clone()), &false);
+        assert_eq!(client.get_claim_hook(), Some(hook));
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &300i128, &None);
+
+        assert_eq!(hook_client.last_claim(), Some((vault_id, 300i128, beneficiary)));
+    }
+
+    #[test]
+    fn test_failing_claim_hook_does_not_revert_claim() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let hook = env.register(FailingClaimHook, ());
+        client.set_claim_hook(&Some(hook), &false);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &300i128, &None);
+
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&beneficiary), 300i128);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_failing_claim_hook_reverts_claim_when_strict() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let hook = env.register(FailingClaimHook, ());
+        client.set_claim_hook(&Some(hook), &true);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &300i128, &None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Creation pause
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_creation_pause_still_allows_claims() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.toggle_creation_pause();
+        assert!(client.is_creation_paused());
+        assert!(!client.is_paused());
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        assert_eq!(client.claim_tokens(&vault_id, &500i128, &None), 500i128);
+
+        client.toggle_creation_pause();
+        assert!(!client.is_creation_paused());
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault creation is paused")]
+    fn test_creation_pause_blocks_create() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        client.toggle_creation_pause();
+        client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault creation is paused")]
+    fn test_creation_pause_blocks_lazy_create() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        client.toggle_creation_pause();
+        client.create_vault_lazy(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused - all withdrawals are disabled")]
+    fn test_pause_allows_creation_but_blocks_claims() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        client.toggle_pause();
+        assert!(!client.is_creation_paused());
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &500i128, &None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Owner claim cooldown
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Owner cooldown active")]
+    fn test_owner_cooldown_spans_vaults() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.set_owner_claim_cooldown(&3_600u64);
+        let v1 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&v1, &100i128, &None);
+        client.claim_tokens(&v2, &100i128, &None);
+    }
+
+    #[test]
+    fn test_owner_cooldown_elapses_and_is_per_owner() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.set_owner_claim_cooldown(&100u64);
+        assert_eq!(client.get_owner_claim_cooldown(), 100u64);
+        let v1 = client.create_vault_full(
+            &alice, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v2 = client.create_vault_full(
+            &alice, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let v3 = client.create_vault_full(
+            &bob, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&v1, &100i128, &None);
+        client.claim_tokens(&v3, &100i128, &None);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 600);
+        client.claim_tokens(&v2, &100i128, &None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Solvency
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_solvency_reports_underfunding() {
+        let (env, cid, client, admin) = setup();
+        let token_addr = register_token(&env, &admin);
+        client.set_token(&token_addr);
+        mint_to(&env, &token_addr, &cid, 1_500i128);
+        let now = env.ledger().timestamp();
+
+        client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_solvency(), (1_500i128, 1_000i128));
+
+        client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let (balance, liability) = client.get_solvency();
+        assert_eq!((balance, liability), (1_500i128, 2_000i128));
+        assert!(balance < liability);
+    }
+
+    // -------------------------------------------------------------------------
+    // Absolute milestone amounts
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_absolute_milestones_release_exact_amounts() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_001i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones_abs(
+            &vault_id,
+            &vec![&env, (1u64, 333i128), (2u64, 333i128), (3u64, 335i128)],
+        );
+
+        client.unlock_milestone(&vault_id, &1u64);
+        assert_eq!(client.claim_max(&vault_id), 333i128);
+
+        client.unlock_milestone(&vault_id, &3u64);
+        assert_eq!(client.claim_max(&vault_id), 335i128);
+
+        client.unlock_milestone(&vault_id, &2u64);
+        assert_eq!(client.claim_max(&vault_id), 333i128);
+
+        assert_eq!(client.get_vault(&vault_id).released_amount, 1_001i128);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&beneficiary), 1_001i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Total milestone amount exceeds vault total")]
+    fn test_absolute_milestones_cannot_exceed_total() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones_abs(&vault_id, &vec![&env, (1u64, 600i128), (2u64, 401i128)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Milestone amount exceeds vault total")]
+    fn test_absolute_milestone_amount_cannot_exceed_total() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones_abs(&vault_id, &vec![&env, (1u64, i128::MAX), (2u64, 1i128)]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Cancel admin proposal
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_cancel_admin_proposal_clears_and_emits() {
+        use soroban_sdk::testutils::Events;
+        use soroban_sdk::IntoVal;
+
+        let (env, cid, client, _admin) = setup();
+        let wrong_admin = Address::generate(&env);
+
+        client.propose_new_admin(&wrong_admin);
+        assert_eq!(client.get_proposed_admin(), Some(wrong_admin.clone()));
+
+        client.cancel_admin_proposal();
+        assert_eq!(
+            env.events().all().filter_by_contract(&cid),
+            vec![
+                &env,
+                (
+                    cid.clone(),
+                    (Symbol::new(&env, "AdminProposalCancelled"),).into_val(&env),
+                    wrong_admin.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_proposed_admin(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "No proposed admin found")]
+    fn test_accept_after_cancelled_proposal_panics() {
+        let (env, _cid, client, _admin) = setup();
+
+        client.propose_new_admin(&Address::generate(&env));
+        client.cancel_admin_proposal();
+        client.accept_ownership();
+    }
+
+    // -------------------------------------------------------------------------
+    // Admin transfer delay
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Admin transfer delay not elapsed")]
+    fn test_accept_ownership_before_delay_panics() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        client.set_admin_transfer_delay(&86_400u64);
+        client.propose_new_admin(&Address::generate(&env));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 86_399);
+        client.accept_ownership();
+    }
+
+    #[test]
+    fn test_accept_ownership_after_delay_succeeds() {
+        let (env, _cid, client, _admin) = setup();
+        let new_admin = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.set_admin_transfer_delay(&86_400u64);
+        assert_eq!(client.get_admin_transfer_delay(), 86_400u64);
+        client.propose_new_admin(&new_admin);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 86_400);
+        client.accept_ownership();
+        assert_eq!(client.get_admin(), new_admin);
+        assert_eq!(client.get_proposed_admin(), None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Frozen vault enumeration
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_frozen_vaults_lists_only_frozen() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let mut ids = Vec::new(&env);
+        for _ in 0..3 {
+            ids.push_back(client.create_vault_full(
+                &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+                &0i128, &true, &false, &0u64,
+            ));
+        }
+        client.freeze_vault(&ids.get(0).unwrap());
+        client.freeze_vault(&ids.get(2).unwrap());
+
+        assert_eq!(
+            client.get_frozen_vaults(&1u64, &10u32),
+            vec![&env, ids.get(0).unwrap(), ids.get(2).unwrap()]
+        );
+        assert_eq!(client.get_frozen_vaults(&2u64, &1u32), Vec::<u64>::new(&env));
+
+        client.unfreeze_vault(&ids.get(0).unwrap());
+        assert_eq!(client.get_frozen_vaults(&1u64, &10u32), vec![&env, ids.get(2).unwrap()]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Batch freeze / unfreeze
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_batch_freeze_skips_already_frozen() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let mut ids = Vec::new(&env);
+        for _ in 0..3 {
+            ids.push_back(client.create_vault_full(
+                &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+                &0i128, &true, &false, &0u64,
+            ));
+        }
+        client.freeze_vault(&ids.get(1).unwrap());
+
+        assert_eq!(client.batch_freeze(&ids), 2u32);
+        for vault_id in ids.iter() {
+            assert!(client.is_vault_frozen(&vault_id));
+        }
+
+        client.unfreeze_vault(&ids.get(0).unwrap());
+        assert_eq!(client.batch_unfreeze(&ids), 2u32);
+        for vault_id in ids.iter() {
+            assert!(!client.is_vault_frozen(&vault_id));
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Milestones after time-based claims
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Cannot set milestones after tokens were released")]
+    fn test_set_milestones_after_release_panics() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &500i128, &None);
+
+        // 500 was already released under time vesting; switching models now is rejected.
+        client.set_milestones(
+            &vault_id,
+            &vec![&env, Milestone { id: 1, percentage: 100, is_unlocked: false }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot set milestones after tokens were released")]
+    fn test_set_milestones_abs_after_release_panics() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &500i128, &None);
+
+        client.set_milestones_abs(&vault_id, &vec![&env, (1u64, 1_000i128)]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Two-step vault transfer
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_offer_and_accept_transfer() {
+        let (env, _cid, client, _admin) = setup();
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &alice, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.set_transfer_acceptance_required(&vault_id, &true);
+
+        client.offer_transfer(&vault_id, &bob);
+        assert_eq!(client.get_pending_transfer(&vault_id), Some((alice.clone(), bob.clone())));
+
+        client.accept_transfer(&vault_id);
+        assert_eq!(client.get_vault(&vault_id).owner, bob);
+        assert_eq!(client.get_user_vaults(&alice), Vec::<u64>::new(&env));
+        assert_eq!(client.get_user_vaults(&bob), vec![&env, vault_id]);
+        assert_eq!(client.get_pending_transfer(&vault_id), None);
+    }
+
+    #[test]
+    fn test_unaccepted_offer_keeps_owner() {
+        let (env, _cid, client, _admin) = setup();
+        let alice = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &alice, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.offer_transfer(&vault_id, &Address::generate(&env));
+
+        assert_eq!(client.get_vault(&vault_id).owner, alice);
+        assert_eq!(client.get_user_vaults(&alice), vec![&env, vault_id]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfer requires recipient acceptance")]
+    fn test_instant_transfer_blocked_when_acceptance_required() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.set_transfer_acceptance_required(&vault_id, &true);
+        client.transfer_vault(&vault_id, &Address::generate(&env));
+    }
+
+    // -------------------------------------------------------------------------
+    // Staking rewards
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_staking_rewards_pays_beneficiary() {
+        let (env, cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        mint_to(&env, &token_addr, &staking, 75i128);
+        MockStakingContractClient::new(&env, &staking).set_reward(&token_addr, &cid, &75i128);
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &500i128, &validator);
+
+        let token_client = token::Client::new(&env, &token_addr);
+        let contract_balance = token_client.balance(&cid);
+        assert_eq!(client.claim_staking_rewards(&vault_id, &validator), 75i128);
+        assert_eq!(token_client.balance(&beneficiary), 75i128);
+        assert_eq!(token_client.balance(&cid), contract_balance);
+
+        // Nothing left to claim
+        assert_eq!(client.claim_staking_rewards(&vault_id, &validator), 0i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Staking rewards not received")]
+    fn test_claim_staking_rewards_requires_transfer() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        // Rewards are reported but sent elsewhere, so the vault must not pay them from principal
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        mint_to(&env, &token_addr, &staking, 75i128);
+        MockStakingContractClient::new(&env, &staking)
+            .set_reward(&token_addr, &Address::generate(&env), &75i128);
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.claim_staking_rewards(&vault_id, &Address::generate(&env));
+    }
+
+    // -------------------------------------------------------------------------
+    // Max vaults per user
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "User vault limit reached")]
+    fn test_max_vaults_per_user_blocks_extra_vault() {
+        let (env, _cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.set_max_vaults_per_user(&2u32);
+        for _ in 0..3 {
+            client.create_vault_full(
+                &owner, &1_000i128, &now, &(now + 1_000),
+                &0i128, &true, &false, &0u64,
+            );
+        }
+    }
+
+    #[test]
+    fn test_raising_max_vaults_per_user_allows_more() {
+        let (env, _cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.set_max_vaults_per_user(&1u32);
+        client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.set_max_vaults_per_user(&2u32);
+        assert_eq!(client.get_max_vaults_per_user(), 2u32);
+        client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_user_vaults(&owner).len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "User vault limit reached")]
+    fn test_max_vaults_per_user_counts_batch_duplicates() {
+        let (env, _cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+
+        client.set_max_vaults_per_user(&1u32);
+        let batch = BatchCreateData {
+            recipients:     vec![&env, owner.clone(), owner.clone()],
+            amounts:        vec![&env, 1_000i128, 2_000i128],
+            start_times:    vec![&env, 100u64, 150u64],
+            end_times:      vec![&env, 200u64, 250u64],
+            keeper_fees:    vec![&env, 0i128, 0i128],
+            step_durations: vec![&env, 0u64, 0u64],
+        };
+        client.batch_create_vaults_full(&batch);
+    }
+
+    // -------------------------------------------------------------------------
+    // Read-only user vault view
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_user_vaults_view_does_not_initialize() {
+        let (env, _cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let full_id = client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let lazy_id = client.create_vault_lazy(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        assert_eq!(client.get_user_vaults_view(&owner), vec![&env, full_id]);
+        let lazy = client.export_vault_state(&lazy_id, &1u32).get(0).unwrap();
+        assert!(!lazy.vault.is_initialized);
+    }
+
+    // -------------------------------------------------------------------------
+    // Fair revoke
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_fair_revoke_splits_vested_and_unvested() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 300);
+        client.claim_tokens(&vault_id, &100i128, &None);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 400);
+        assert_eq!(client.fair_revoke(&vault_id), (300i128, 600i128));
+
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&beneficiary), 400i128);
+        assert_eq!(client.get_contract_state(), (0i128, 1_000i128, 999_600i128));
+        assert_eq!(client.get_vault(&vault_id).released_amount, 1_000i128);
+    }
+
+    #[test]
+    fn test_fair_revoke_before_start_returns_everything() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &(now + 100), &(now + 1_100),
+            &0i128, &true, &false, &0u64,
+        );
+
+        assert_eq!(client.fair_revoke(&vault_id), (0i128, 1_000i128));
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&beneficiary), 0i128);
+        assert_eq!(client.get_contract_state(), (0i128, 1_000i128, 1_000_000i128));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot revoke frozen vault")]
+    fn test_fair_revoke_rejects_frozen_vault() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.freeze_vault(&vault_id);
+        client.fair_revoke(&vault_id);
+    }
+
+    // -------------------------------------------------------------------------
+    // Claim deadline and expiry sweep
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Claim deadline passed")]
+    fn test_claim_after_deadline_panics() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        client.claim_tokens(&vault_id, &100i128, &None);
+    }
+
+    #[test]
+    fn test_sweep_expired_recovers_unreleased() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+        assert_eq!(client.get_claim_deadline(&vault_id), now + 2_000);
+
+        // Claims work normally before the deadline
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&vault_id, &250i128, &None);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        assert_eq!(client.sweep_expired(&vault_id), 750i128);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 1_000i128);
+        assert_eq!(client.get_contract_state(), (0i128, 1_000i128, 999_750i128));
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim deadline not reached")]
+    fn test_sweep_expired_before_deadline_panics() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+        client.sweep_expired(&vault_id);
+    }
+
+    // -------------------------------------------------------------------------
+    // Vault categories
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_vaults_by_category_filters() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+        let team = Symbol::new(&env, "team");
+        let advisors = Symbol::new(&env, "advisors");
+
+        let t1 = client.create_vault_with_category(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64, &team,
+        );
+        let a1 = client.create_vault_with_category(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64, &advisors,
+        );
+        let t2 = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_vault(&t2).category, Symbol::new(&env, ""));
+        client.set_vault_category(&t2, &team);
+
+        assert_eq!(client.get_vaults_by_category(&team, &1u64, &10u32), vec![&env, t1, t2]);
+        assert_eq!(client.get_vaults_by_category(&advisors, &1u64, &10u32), vec![&env, a1]);
+        assert_eq!(client.get_vault(&a1).category, advisors);
+    }
+
+    // -------------------------------------------------------------------------
+    // Claim readiness
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_is_claim_ready_false_without_token() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        assert!(!client.is_claim_ready(&vault_id));
+    }
+
+    #[test]
+    fn test_is_claim_ready_tracks_freeze_and_unlock() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &(now + 100), &(now + 1_100),
+            &0i128, &true, &false, &0u64,
+        );
+        // Nothing unlocked before start
+        assert!(!client.is_claim_ready(&vault_id));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 600);
+        assert!(client.is_claim_ready(&vault_id));
+
+        client.freeze_vault(&vault_id);
+        assert!(!client.is_claim_ready(&vault_id));
+
+        client.unfreeze_vault(&vault_id);
+        client.toggle_pause();
+        assert!(!client.is_claim_ready(&vault_id));
+        assert!(!client.is_claim_ready(&99u64));
+    }
+
+    // -------------------------------------------------------------------------
+    // Step duration correction
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_set_step_duration_before_start() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &(now + 100), &(now + 100 + 2_419_200),
+            &0i128, &true, &false, &604_800u64,
+        );
+        client.set_step_offset(&vault_id, &86_400u64);
+
+        client.set_step_duration(&vault_id, &2_419_200u64);
+        let vault = client.get_vault(&vault_id);
+        assert_eq!(vault.step_duration, 2_419_200u64);
+        assert_eq!(vault.step_offset, 86_400u64);
+
+        // Switching to linear drops the offset
+        client.set_step_duration(&vault_id, &0u64);
+        assert_eq!(client.get_vault(&vault_id).step_offset, 0u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vesting has already started")]
+    fn test_set_step_duration_after_start_panics() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &100u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 1);
+        client.set_step_duration(&vault_id, &200u64);
+    }
+
+    // -------------------------------------------------------------------------
+    // Relayed claims
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_via_relayer_splits_payout() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let relayer = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+
+        assert_eq!(client.claim_via_relayer(&vault_id, &400i128, &relayer, &5i128), 400i128);
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&relayer), 5i128);
+        assert_eq!(token_client.balance(&beneficiary), 395i128);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 400i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Relayer fee must be less than the payout")]
+    fn test_claim_via_relayer_fee_exceeds_payout() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_via_relayer(&vault_id, &100i128, &Address::generate(&env), &100i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Milestone count limit
+    // -------------------------------------------------------------------------
+
+    fn milestone_list(env: &Env, count: u32) -> Vec<Milestone> {
+        let mut milestones = Vec::new(env);
+        for id in 0..count {
+            milestones.push_back(Milestone { id: id as u64, percentage: 1, is_unlocked: false });
+        }
+        milestones
+    }
+
+    #[test]
+    fn test_max_milestones_accepted() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones(&vault_id, &milestone_list(&env, MAX_MILESTONES));
+        assert_eq!(client.get_milestones(&vault_id).len(), MAX_MILESTONES);
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many milestones")]
+    fn test_too_many_milestones_panics() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones(&vault_id, &milestone_list(&env, MAX_MILESTONES + 1));
+    }
+
+    // -------------------------------------------------------------------------
+    // Running contract totals
+    // -------------------------------------------------------------------------
+
+    fn scanned_totals(client: &VestingContractClient) -> (i128, i128) {
+        let mut locked = 0i128;
+        let mut claimed = 0i128;
+        for record in client.export_vault_state(&1u64, &100u32).iter() {
+            let vault = record.vault;
+            locked += vault.total_amount - vault.released_amount;
+            claimed += vault.released_amount;
+        }
+        (locked, claimed)
+    }
+
+    #[test]
+    fn test_running_totals_match_scan() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let mut ids = Vec::new(&env);
+        for amount in [1_000i128, 2_000i128, 3_000i128] {
+            ids.push_back(client.create_vault_full(
+                &Address::generate(&env), &amount, &now, &(now + 1_000),
+                &0i128, &true, &true, &0u64,
+            ));
+        }
+        let (locked, claimed, _) = client.get_contract_state();
+        assert_eq!((locked, claimed), scanned_totals(&client));
+        assert_eq!((locked, claimed), (6_000i128, 0i128));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&ids.get(0).unwrap(), &400i128, &None);
+        client.revoke_partial(&ids.get(1).unwrap(), &500i128);
+        client.revoke_tokens(&ids.get(2).unwrap());
+        client.split_vault(&ids.get(0).unwrap(), &100i128, &Address::generate(&env));
+
+        let (locked, claimed, admin_balance) = client.get_contract_state();
+        assert_eq!((locked, claimed), scanned_totals(&client));
+        assert_eq!(locked, 600i128 + 1_500i128);
+        assert_eq!(admin_balance, 1_000_000i128 - 6_000i128 + 500i128 + 3_000i128);
+        assert!(client.check_invariant());
+    }
+
+    #[test]
+    fn test_backfill_running_totals_restores_scan() {
+        use crate::DataKey;
+
+        let (env, cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.create_vault_full(
+            &Address::generate(&env), &2_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &300i128, &None);
+
+        // Simulate a deployment whose vaults predate the running totals
+        env.as_contract(&cid, || {
+            env.storage().instance().remove(&DataKey::TotalLocked);
+            env.storage().instance().remove(&DataKey::TotalClaimed);
+        });
+        assert_eq!(client.get_contract_state().0, 0i128);
+
+        assert_eq!(client.backfill_running_totals(), (2_700i128, 300i128));
+        let (locked, claimed, _) = client.get_contract_state();
+        assert_eq!((locked, claimed), scanned_totals(&client));
+    }
+
+    #[test]
+    #[should_panic(expected = "Running totals already backfilled")]
+    fn test_backfill_running_totals_runs_once() {
+        let (_env, _cid, client, _admin) = setup();
+        client.backfill_running_totals();
+        client.backfill_running_totals();
+    }
+
+    // -------------------------------------------------------------------------
+    // Yield surplus rescue
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_rescue_yield_surplus_overfunded() {
+        let (env, cid, client, admin, token_addr) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        // Overfund by 5_000 on top of the 1_000_000 backing
+        mint_to(&env, &token_addr, &cid, 5_000i128);
+
+        assert_eq!(client.rescue_yield_surplus(), 5_000i128);
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&admin), 5_000i128);
+        assert_eq!(token_client.balance(&cid), 1_000_000i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot rescue surplus while tokens are staked")]
+    fn test_rescue_yield_surplus_rejected_while_staked() {
+        let (env, cid, client, _admin, token_addr) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &500i128, &Address::generate(&env));
+        mint_to(&env, &token_addr, &cid, 5_000i128);
+
+        client.rescue_yield_surplus();
+    }
+
+    // -------------------------------------------------------------------------
+    // Next unlock
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_next_unlock_monthly_steps() {
+        let (env, _cid, client, _admin) = setup();
+        let month = 2_592_000u64;
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &12_000i128, &now, &(now + 12 * month),
+            &0i128, &true, &false, &month,
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = now + month + month / 2);
+        assert_eq!(client.next_unlock(&vault_id), (now + 2 * month, 1_000i128));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 11 * month + 1);
+        assert_eq!(client.next_unlock(&vault_id), (now + 12 * month, 1_000i128));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 12 * month);
+        assert_eq!(client.next_unlock(&vault_id), (now + 12 * month, 0i128));
+    }
+
+    #[test]
+    fn test_next_unlock_linear_rate() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &10_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 10);
+        assert_eq!(client.next_unlock(&vault_id), (now + 10, 10i128));
+    }
+
+    // -------------------------------------------------------------------------
+    // Initialization guard
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Already initialized")]
+    fn test_initialize_twice_reverts() {
+        let (env, _cid, client, _admin) = setup();
+        client.initialize(&Address::generate(&env), &1i128, &false, &false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Initial supply must be non-negative")]
+    fn test_initialize_rejects_negative_supply() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(VestingContract, ());
+        let client = VestingContractClient::new(&env, &contract_id);
+        client.initialize(&Address::generate(&env), &-1i128, &true, &false);
+    }
+
+    // -------------------------------------------------------------------------
+    // New-owner validation
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Cannot transfer to the contract address")]
+    fn test_transfer_vault_to_contract_address_reverts() {
+        let (env, cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.transfer_vault(&vault_id, &cid);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot transfer to the contract address")]
+    fn test_transfer_beneficiary_to_contract_address_reverts() {
+        let (env, cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.transfer_beneficiary(&vault_id, &cid);
+    }
+
+    #[test]
+    #[should_panic(expected = "New owner must differ from current owner")]
+    fn test_transfer_vault_to_current_owner_reverts() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &true, &0u64,
+        );
+        client.transfer_vault(&vault_id, &beneficiary);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delegate cannot be the contract address")]
+    fn test_set_delegate_to_contract_address_reverts() {
+        let (env, cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(cid), &DELEGATE_CLAIM);
+    }
+
+    // -------------------------------------------------------------------------
+    // Protocol fee
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_protocol_fee_split_on_claim() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_protocol_fee(&100u32, &treasury);
+        assert_eq!(client.get_protocol_fee(), Some((100u32, treasury.clone())));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &500i128, &None);
+
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&treasury), 5i128);
+        assert_eq!(token_client.balance(&beneficiary), 495i128);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 500i128);
+    }
+
+    #[test]
+    fn test_protocol_fee_split_on_delegate_claim() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(Address::generate(&env)), &DELEGATE_CLAIM);
+        client.set_protocol_fee(&200u32, &treasury);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_as_delegate(&vault_id, &1_000i128);
+
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&treasury), 20i128);
+        assert_eq!(token_client.balance(&beneficiary), 980i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Protocol fee exceeds maximum")]
+    fn test_protocol_fee_above_cap_rejected() {
+        let (env, _cid, client, _admin) = setup();
+        client.set_protocol_fee(&(MAX_PROTOCOL_FEE_BPS + 1), &Address::generate(&env));
+    }
+
+    // -------------------------------------------------------------------------
+    // Batched claimable query
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_claimable_batch_mixes_valid_and_missing() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let linear_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let milestone_id = client.create_vault_full(
+            &Address::generate(&env), &2_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones(&milestone_id, &vec![
+            &env,
+            Milestone { id: 1, percentage: 25, is_unlocked: false },
+            Milestone { id: 2, percentage: 75, is_unlocked: false },
+        ]);
+        client.unlock_milestone(&milestone_id, &1u64);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 300);
+        let amounts = client.get_claimable_batch(&vec![&env, linear_id, 999u64, milestone_id]);
+        assert_eq!(amounts, vec![&env, 300i128, -1i128, 500i128]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many vault ids")]
+    fn test_get_claimable_batch_rejects_oversized_input() {
+        let (env, _cid, client, _admin) = setup();
+        let mut ids = Vec::new(&env);
+        for id in 0..101u64 {
+            ids.push_back(id);
+        }
+        client.get_claimable_batch(&ids);
+    }
+
+    // -------------------------------------------------------------------------
+    // Lazy vault first claim
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_initializes_lazy_vault() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_lazy(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_user_vaults(&beneficiary).len(), 0);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &200i128, &None);
+
+        assert_eq!(client.get_user_vaults(&beneficiary), vec![&env, vault_id]);
+        let vault = client.get_vault(&vault_id);
+        assert!(vault.is_initialized);
+        assert_eq!(vault.released_amount, 200i128);
+        assert_eq!(client.get_user_vaults(&beneficiary).len(), 1);
+    }
+
+    #[test]
+    fn test_is_claim_ready_for_lazy_vault() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_lazy(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        assert!(client.is_claim_ready(&vault_id));
+    }
+
+    // -------------------------------------------------------------------------
+    // Auto-claim final dust
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_auto_claim_releases_final_dust_without_fee() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let keeper = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &100i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 950);
+        client.auto_claim(&vault_id, &keeper, &0i128);
+
+        // Final 50-token tranche is below the 100-token keeper fee
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.auto_claim(&vault_id, &keeper, &0i128);
+
+        let token_client = token::Client::new(&env, &token_addr);
+        assert_eq!(token_client.balance(&beneficiary), 900i128);
+        assert_eq!(token_client.balance(&keeper), 100i128);
+        assert_eq!(client.get_keeper_fee(&keeper), 100i128);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 1_000i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient claimable tokens to cover fee")]
+    fn test_auto_claim_dust_before_end_still_reverts() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &100i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 50);
+        client.auto_claim(&vault_id, &Address::generate(&env), &0i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Delegate views
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_delegate_and_permissions() {
+        let (env, _cid, client, _admin) = setup();
+        let delegate = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_delegate(&vault_id), None);
+        assert_eq!(client.get_delegate_permissions(&vault_id), 0u32);
+
+        client.set_delegate(&vault_id, &Some(delegate.clone()), &(DELEGATE_CLAIM | DELEGATE_STAKE));
+        assert_eq!(client.get_delegate(&vault_id), Some(delegate));
+        assert_eq!(client.get_delegate_permissions(&vault_id), DELEGATE_CLAIM | DELEGATE_STAKE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault not found")]
+    fn test_get_delegate_missing_vault() {
+        let (_env, _cid, client, _admin) = setup();
+        client.get_delegate(&99u64);
+    }
+
+    // -------------------------------------------------------------------------
+    // Keeper pre-check
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_can_auto_claim_tracks_pause_and_freeze() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &100i128, &true, &false, &0u64,
+        );
+        // Claimable (50) doesn't cover the keeper fee yet
+        env.ledger().with_mut(|l| l.timestamp = now + 50);
+        assert!(!client.can_auto_claim(&vault_id));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        assert!(client.can_auto_claim(&vault_id));
+
+        client.toggle_pause();
+        assert!(!client.can_auto_claim(&vault_id));
+        client.toggle_pause();
+
+        client.freeze_vault(&vault_id);
+        assert!(!client.can_auto_claim(&vault_id));
+        assert!(!client.can_auto_claim(&99u64));
+    }
+
+    // -------------------------------------------------------------------------
+    // Immediate first step
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_first_step_immediate_unlocks_at_start() {
+        let (env, _cid, client, _admin) = setup();
+        let month = 2_592_000u64;
+        let start = env.ledger().timestamp() + 100;
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &12_000i128, &start, &(start + 12 * month),
+            &0i128, &true, &false, &month,
+        );
+        client.set_first_step_immediate(&vault_id, &true);
+
+        env.ledger().with_mut(|l| l.timestamp = start - 1);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0i128);
+
+        env.ledger().with_mut(|l| l.timestamp = start);
+        assert_eq!(client.get_claimable_amount(&vault_id), 1_000i128);
+
+        env.ledger().with_mut(|l| l.timestamp = start + month);
+        assert_eq!(client.get_claimable_amount(&vault_id), 2_000i128);
+
+        env.ledger().with_mut(|l| l.timestamp = start + 11 * month);
+        assert_eq!(client.get_claimable_amount(&vault_id), 12_000i128);
+    }
+
+    #[test]
+    fn test_first_step_immediate_ignored_for_linear_vault_at_start() {
+        let (env, _cid, client, _admin) = setup();
+        let start = env.ledger().timestamp() + 100;
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &12_000i128, &start, &(start + 1_200),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_first_step_immediate(&vault_id, &true);
+
+        // No step to front-load: nothing is unlocked at exactly start_time
+        env.ledger().with_mut(|l| l.timestamp = start);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0i128);
+
+        env.ledger().with_mut(|l| l.timestamp = start + 100);
+        assert_eq!(client.get_claimable_amount(&vault_id), 1_000i128);
+    }
+
+    #[test]
+    fn test_first_step_immediate_defaults_off() {
+        let (env, _cid, client, _admin) = setup();
+        let month = 2_592_000u64;
+        let start = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &12_000i128, &start, &(start + 12 * month),
+            &0i128, &true, &false, &month,
+        );
+        assert!(!client.get_vault(&vault_id).first_step_immediate);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Partial revoke event
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_revoke_partial_emits_partial_revoke_event() {
+        use soroban_sdk::testutils::Events;
+        use soroban_sdk::IntoVal;
+
+        let (env, cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.revoke_partial(&vault_id, &300i128);
+        assert_eq!(
+            env.events().all().filter_by_contract(&cid),
+            vec![
+                &env,
+                (
+                    cid.clone(),
+                    (Symbol::new(&env, "PartialRevoke"), vault_id).into_val(&env),
+                    (300i128, 700i128, now).into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Ownership proof
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_is_owner_and_get_owner() {
+        let (env, _cid, client, _admin) = setup();
+        let beneficiary = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_owner(&vault_id), beneficiary);
+        assert!(client.is_owner(&vault_id, &beneficiary));
+        assert!(!client.is_owner(&vault_id, &Address::generate(&env)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault not found")]
+    fn test_is_owner_missing_vault() {
+        let (env, _cid, client, _admin) = setup();
+        client.is_owner(&99u64, &Address::generate(&env));
+    }
+
+    // -------------------------------------------------------------------------
+    // Migration exclusion list
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_migrate_liquidity_skips_excluded_token() {
+        let (env, cid, client, admin) = setup();
+        let main_token = register_token(&env, &admin);
+        let reward_token = register_token(&env, &admin);
+        mint_to(&env, &main_token, &cid, 5_000i128);
+        mint_to(&env, &reward_token, &cid, 700i128);
+        client.add_to_whitelist(&main_token);
+        client.add_to_whitelist(&reward_token);
+
+        client.set_migration_excluded(&reward_token, &true);
+        assert!(client.is_migration_excluded(&reward_token));
+        assert!(!client.is_migration_excluded(&main_token));
+
+        let v2 = env.register(VestingContract, ());
+        let migrated = client.migrate_liquidity(&v2);
+        assert_eq!(migrated.get(main_token.clone()), Some(5_000i128));
+        assert_eq!(migrated.get(reward_token.clone()), Some(0i128));
+
+        let main_client = token::Client::new(&env, &main_token);
+        let reward_client = token::Client::new(&env, &reward_token);
+        assert_eq!(main_client.balance(&v2), 5_000i128);
+        assert_eq!(main_client.balance(&cid), 0i128);
+        assert_eq!(reward_client.balance(&v2), 0i128);
+        assert_eq!(reward_client.balance(&cid), 700i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Milestone mode
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_is_milestone_vault() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert!(!client.is_milestone_vault(&vault_id));
+
+        client.set_milestones(&vault_id, &milestone_list(&env, 2));
+        assert!(client.is_milestone_vault(&vault_id));
+    }
+
+    // -------------------------------------------------------------------------
+    // Batch total overflow
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Batch total overflow")]
+    fn test_batch_create_rejects_overflowing_total() {
+        let (env, _cid, client, _admin) = setup();
+
+        let batch = BatchCreateData {
+            recipients: vec![&env, Address::generate(&env), Address::generate(&env)],
+            amounts: vec![&env, i128::MAX, 2i128],
+            start_times: vec![&env, 0u64, 0u64],
+            end_times: vec![&env, 1_000u64, 1_000u64],
+            keeper_fees: vec![&env, 0i128, 0i128],
+            step_durations: vec![&env, 0u64, 0u64],
+        };
+        client.batch_create_vaults_full(&batch);
+    }
+
+    #[test]
+    #[should_panic(expected = "Batch total overflow")]
+    fn test_batch_create_lazy_rejects_overflowing_total() {
+        let (env, _cid, client, _admin) = setup();
+
+        let batch = BatchCreateData {
+            recipients: vec![&env, Address::generate(&env), Address::generate(&env)],
+            amounts: vec![&env, i128::MAX / 2 + 1, i128::MAX / 2 + 1],
+            start_times: vec![&env, 0u64, 0u64],
+            end_times: vec![&env, 1_000u64, 1_000u64],
+            keeper_fees: vec![&env, 0i128, 0i128],
+            step_durations: vec![&env, 0u64, 0u64],
+        };
+        client.batch_create_vaults_lazy(&batch);
+    }
+
+    // -------------------------------------------------------------------------
+    // Aggregate keeper fees
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_total_keeper_fees_across_keepers() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let keeper_a = Address::generate(&env);
+        let keeper_b = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_a = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &30i128, &true, &false, &0u64,
+        );
+        let vault_b = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &50i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_total_keeper_fees(), 0i128);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 300);
+        client.auto_claim(&vault_a, &keeper_a, &0i128);
+        client.auto_claim(&vault_b, &keeper_b, &0i128);
+        env.ledger().with_mut(|l| l.timestamp = now + 600);
+        client.auto_claim(&vault_a, &keeper_b, &0i128);
+
+        assert_eq!(client.get_keeper_fee(&keeper_a), 30i128);
+        assert_eq!(client.get_keeper_fee(&keeper_b), 80i128);
+        assert_eq!(
+            client.get_total_keeper_fees(),
+            client.get_keeper_fee(&keeper_a) + client.get_keeper_fee(&keeper_b)
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Fully unlocked milestones
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_all_milestones_unlocked_claims_exact_total() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_001i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones(&vault_id, &vec![
+            &env,
+            Milestone { id: 1, percentage: 33, is_unlocked: false },
+            Milestone { id: 2, percentage: 33, is_unlocked: false },
+            Milestone { id: 3, percentage: 34, is_unlocked: false },
+        ]);
+        client.unlock_milestone(&vault_id, &1u64);
+        client.unlock_milestone(&vault_id, &2u64);
+        client.unlock_milestone(&vault_id, &3u64);
+
+        assert_eq!(client.get_claimable_batch(&vec![&env, vault_id]), vec![&env, 1_001i128]);
+        client.claim_tokens(&vault_id, &1_001i128, &None);
+        assert_eq!(client.get_vault(&vault_id).released_amount, 1_001i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Recovery address
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_recover_vault_after_inactivity() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let beneficiary = Address::generate(&env);
+        let recovery = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &beneficiary, &1_000i128, &now, &(now + 10_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_recovery_inactivity_period(&1_000u64);
+        client.set_recovery_address(&vault_id, &recovery);
+        assert_eq!(client.get_recovery_address(&vault_id), Some(recovery.clone()));
+
+        // A claim resets the inactivity clock
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &10i128, &None);
+        assert_eq!(client.get_last_activity(&vault_id), now + 500);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_500);
+        client.recover_vault(&vault_id);
+
+        assert_eq!(client.get_owner(&vault_id), recovery);
+        assert_eq!(client.get_recovery_address(&vault_id), None);
+        assert_eq!(client.get_user_vaults(&recovery), vec![&env, vault_id]);
+        assert_eq!(client.get_user_vaults(&beneficiary).len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vault is still active")]
+    fn test_recover_vault_before_inactivity_window() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let recovery = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 10_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_recovery_inactivity_period(&1_000u64);
+        client.set_recovery_address(&vault_id, &recovery);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &10i128, &None);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 1_200);
+        client.recover_vault(&vault_id);
+    }
+
+    // -------------------------------------------------------------------------
+    // Funded vault creation
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_create_vault_funded_pulls_tokens_from_admin() {
+        let (env, cid, client, admin, token_addr) = setup_with_token();
+        mint_to(&env, &token_addr, &admin, 5_000i128);
+        let token_client = token::Client::new(&env, &token_addr);
+        let contract_before = token_client.balance(&cid);
+        let (_locked, _claimed, admin_balance_before) = client.get_contract_state();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_funded(
+            &Address::generate(&env), &3_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        assert_eq!(token_client.balance(&cid), contract_before + 3_000i128);
+        assert_eq!(token_client.balance(&admin), 2_000i128);
+        assert_eq!(client.get_vault(&vault_id).total_amount, 3_000i128);
+        let (_locked, _claimed, admin_balance_after) = client.get_contract_state();
+        assert_eq!(admin_balance_after, admin_balance_before);
+        assert!(client.check_invariant());
+    }
+
+    // -------------------------------------------------------------------------
+    // Upcoming full unlocks
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_vaults_vesting_before_cutoff() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let mut ids = Vec::new(&env);
+        for end in [1_000u64, 2_000, 3_000, 4_000] {
+            ids.push_back(client.create_vault_full(
+                &Address::generate(&env), &1_000i128, &now, &(now + end),
+                &0i128, &true, &false, &0u64,
+            ));
+        }
+        let cutoff = now + 3_000;
+        assert_eq!(
+            client.vaults_vesting_before(&cutoff, &1u64, &10u32),
+            vec![&env, ids.get(0).unwrap(), ids.get(1).unwrap(), ids.get(2).unwrap()]
+        );
+
+        // Fully released vaults drop out
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        client.claim_tokens(&ids.get(0).unwrap(), &1_000i128, &None);
+        assert_eq!(
+            client.vaults_vesting_before(&cutoff, &1u64, &10u32),
+            vec![&env, ids.get(1).unwrap(), ids.get(2).unwrap()]
+        );
+        assert_eq!(
+            client.vaults_vesting_before(&cutoff, &3u64, &1u32),
+            vec![&env, ids.get(2).unwrap()]
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Unstake order
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_claim_unstake_order_drains_listed_validator_first() {
+        use soroban_sdk::testutils::Events;
+        use soroban_sdk::IntoVal;
+
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let validator_a = Address::generate(&env);
+        let validator_b = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &300i128, &validator_a);
+        client.stake_tokens(&vault_id, &300i128, &validator_b);
+
+        // 400 liquid, so claiming 600 needs 200 unstaked
+        env.ledger().with_mut(|l| l.timestamp = now + 600);
+        client.claim_tokens(&vault_id, &600i128, &Some(vec![&env, validator_b.clone()]));
+        assert_eq!(
+            env.events().all().filter_by_contract(&staking),
+            vec![
+                &env,
+                (
+                    staking.clone(),
+                    (Symbol::new(&env, "unstake_from"), vault_id).into_val(&env),
+                    (200i128, validator_b.clone()).into_val(&env),
+                ),
+            ]
+        );
+
+        let stakes = client.get_validator_stakes(&vault_id);
+        assert_eq!(stakes.get(validator_a), Some(300i128));
+        assert_eq!(stakes.get(validator_b), Some(100i128));
+        assert_eq!(client.get_vault(&vault_id).staked_amount, 400i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient unlocked tokens to claim")]
+    fn test_claim_unstake_order_rejects_over_claim_before_unstaking() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let validator = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &600i128, &validator);
+
+        // Only 500 vested: the claim is rejected before any stake is pulled back
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_tokens(&vault_id, &700i128, &Some(vec![&env, validator]));
+    }
+
+    // -------------------------------------------------------------------------
+    // Governance snapshot
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_snapshot_holdings_sums_user_vaults() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let holder = Address::generate(&env);
+        let other = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let first = client.create_vault_full(
+            &holder, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.create_vault_full(
+            &holder, &2_500i128, &now, &(now + 2_000),
+            &0i128, &true, &false, &0u64,
+        );
+        env.ledger().with_mut(|l| l.timestamp = now + 400);
+        client.claim_tokens(&first, &400i128, &None);
+
+        // first: 400 vested, 400 released; second: 500 vested
+        let snapshot = client.snapshot_holdings(&vec![&env, holder, other]);
+        assert_eq!(snapshot, vec![&env, 500i128, 0i128]);
+    }
+
+    #[test]
+    fn test_snapshot_holdings_counts_lazy_and_milestone_vaults() {
+        let (env, _cid, client, _admin) = setup();
+        let holder = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        client.create_vault_lazy(
+            &holder, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let milestone_vault = client.create_vault_full(
+            &holder, &2_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_milestones(
+            &milestone_vault,
+            &vec![
+                &env,
+                Milestone { id: 1, percentage: 25, is_unlocked: false },
+                Milestone { id: 2, percentage: 75, is_unlocked: false },
+            ],
+        );
+        client.unlock_milestone(&milestone_vault, &1u64);
+        env.ledger().with_mut(|l| l.timestamp = now + 300);
+
+        // lazy: 300 time-vested; milestone vault: 25% of 2_000 regardless of time
+        let snapshot = client.snapshot_holdings(&vec![&env, holder]);
+        assert_eq!(snapshot, vec![&env, 800i128]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Extend and increase
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_extend_and_increase_rebases_rate() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let (_locked, _claimed, admin_before) = client.get_contract_state();
+
+        client.extend_and_increase(&vault_id, &(now + 2_000), &2_000i128);
+
+        let vault = client.get_vault(&vault_id);
+        assert_eq!(vault.end_time, now + 2_000);
+        assert_eq!(vault.total_amount, 3_000i128);
+        let (_locked, _claimed, admin_after) = client.get_contract_state();
+        assert_eq!(admin_after, admin_before - 2_000i128);
+
+        // 3_000 over 2_000s vests 1.5 tokens per second
+        env.ledger().with_mut(|l| l.timestamp = now + 1_000);
+        assert_eq!(client.get_claimable_amount(&vault_id), 1_500i128);
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        assert_eq!(client.get_claimable_amount(&vault_id), 3_000i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "New end time must not be earlier than current end time")]
+    fn test_extend_and_increase_rejects_shorter_schedule() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.extend_and_increase(&vault_id, &(now + 500), &100i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Revoking staked vaults
+    // -------------------------------------------------------------------------
+
+    #[test]
+    #[should_panic(expected = "Unstake before revoking")]
+    fn test_revoke_staked_vault_reverts() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &300i128, &Address::generate(&env));
+        client.revoke_tokens(&vault_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unstake before revoking")]
+    fn test_sweep_expired_staked_vault_reverts() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &300i128, &Address::generate(&env));
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        client.sweep_expired(&vault_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unstake before revoking")]
+    fn test_batch_revoke_staked_vault_reverts() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &300i128, &Address::generate(&env));
+        client.batch_revoke(&vec![&env, vault_id]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unstake before revoking")]
+    fn test_revoke_partial_staked_vault_reverts() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &300i128, &Address::generate(&env));
+        client.revoke_partial(&vault_id, &100i128);
+    }
+
+    #[test]
+    fn test_revoke_after_unstaking() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let now = env.ledger().timestamp();
+
+        let staking = env.register(MockStakingContract, ());
+        client.set_staking_contract(&staking);
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.stake_tokens(&vault_id, &300i128, &Address::generate(&env));
+        client.unstake_tokens(&vault_id, &300i128);
+        assert_eq!(client.get_vault(&vault_id).staked_amount, 0i128);
+
+        assert_eq!(client.revoke_tokens(&vault_id), 1_000i128);
+    }
+
+    // -------------------------------------------------------------------------
+    // Cliff unlock percentage
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_cliff_unlock_bps_slice_at_cliff_then_linear() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+        let cliff = 100u64;
+        let start = now + cliff;
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &10_000i128, &start, &(start + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_cliff_unlock_bps(&vault_id, &1_000u32);
+        assert_eq!(client.get_vault(&vault_id).cliff_unlock_bps, 1_000u32);
+
+        env.ledger().with_mut(|l| l.timestamp = start - 1);
+        assert_eq!(client.get_claimable_amount(&vault_id), 0i128);
+
+        // Exactly the 10% slice at start_time + cliff
+        env.ledger().with_mut(|l| l.timestamp = now + cliff);
+        assert_eq!(client.get_claimable_amount(&vault_id), 1_000i128);
+
+        // Remaining 9_000 vests linearly over the 1_000s duration
+        env.ledger().with_mut(|l| l.timestamp = start + 250);
+        assert_eq!(client.get_claimable_amount(&vault_id), 3_250i128);
+        env.ledger().with_mut(|l| l.timestamp = start + 500);
+        assert_eq!(client.get_claimable_amount(&vault_id), 5_500i128);
+        env.ledger().with_mut(|l| l.timestamp = start + 1_000);
+        assert_eq!(client.get_claimable_amount(&vault_id), 10_000i128);
+    }
+
+    #[test]
+    fn test_cliff_unlock_bps_on_step_vault() {
+        let (env, _cid, client, _admin) = setup();
+        let start = env.ledger().timestamp() + 100;
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &10_000i128, &start, &(start + 1_000),
+            &0i128, &true, &false, &250u64,
+        );
+        client.set_cliff_unlock_bps(&vault_id, &2_000u32);
+
+        // The slice is the only thing unlocked until the first step completes
+        env.ledger().with_mut(|l| l.timestamp = start);
+        assert_eq!(client.get_claimable_amount(&vault_id), 2_000i128);
+        env.ledger().with_mut(|l| l.timestamp = start + 249);
+        assert_eq!(client.get_claimable_amount(&vault_id), 2_000i128);
+
+        // Each of the four steps releases a quarter of the remaining 8_000
+        env.ledger().with_mut(|l| l.timestamp = start + 250);
+        assert_eq!(client.get_claimable_amount(&vault_id), 4_000i128);
+        env.ledger().with_mut(|l| l.timestamp = start + 1_000);
+        assert_eq!(client.get_claimable_amount(&vault_id), 10_000i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cliff unlock exceeds 100%")]
+    fn test_cliff_unlock_bps_rejects_over_100_percent() {
+        let (env, _cid, client, _admin) = setup();
+        let start = env.ledger().timestamp() + 100;
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &10_000i128, &start, &(start + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_cliff_unlock_bps(&vault_id, &10_001u32);
+    }
+
+    // -------------------------------------------------------------------------
+    // Emergency delegate clear
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_emergency_clear_delegate_by_owner_freezes_vault() {
+        use soroban_sdk::testutils::Events;
+        use soroban_sdk::IntoVal;
+
+        let (env, cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+
+        client.set_delegate(&vault_id, &Some(delegate.clone()), &DELEGATE_CLAIM);
+        client.emergency_clear_delegate(&vault_id, &owner);
+        assert_eq!(env.auths()[0].0, owner);
+        assert_eq!(
+            env.events().all().filter_by_contract(&cid),
+            vec![
+                &env,
+                (
+                    cid.clone(),
+                    (Symbol::new(&env, "DelegateRevoked"), vault_id).into_val(&env),
+                    (delegate.clone(), owner.clone(), now).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_delegate(&vault_id), None);
+        assert_eq!(client.get_delegate_permissions(&vault_id), 0u32);
+        assert!(client.is_vault_frozen(&vault_id));
+    }
+
+    #[test]
+    fn test_emergency_clear_delegate_by_admin_freezes_vault() {
+        let (env, _cid, client, admin) = setup();
+        let owner = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(Address::generate(&env)), &DELEGATE_CLAIM);
+
+        client.emergency_clear_delegate(&vault_id, &admin);
+        assert_eq!(env.auths()[0].0, admin);
+        assert_eq!(client.get_delegate(&vault_id), None);
+        assert!(client.is_vault_frozen(&vault_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or admin can clear the delegate")]
+    fn test_emergency_clear_delegate_rejects_other_caller() {
+        let (env, _cid, client, _admin) = setup();
+        let delegate = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(delegate.clone()), &DELEGATE_CLAIM);
+        client.emergency_clear_delegate(&vault_id, &delegate);
+    }
+
+    #[test]
+    #[should_panic(expected = "No delegate set for this vault")]
+    fn test_emergency_clear_delegate_blocks_delegate_claim() {
+        let (env, _cid, client, _admin, _token) = setup_with_token();
+        let owner = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_delegate(&vault_id, &Some(Address::generate(&env)), &DELEGATE_CLAIM);
+        client.emergency_clear_delegate(&vault_id, &owner);
+        client.unfreeze_vault(&vault_id);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 500);
+        client.claim_as_delegate(&vault_id, &100i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "No delegate set for this vault")]
+    fn test_emergency_clear_delegate_rejects_vault_without_delegate() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let owner = Address::generate(&env);
+        let vault_id = client.create_vault_full(
+            &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.emergency_clear_delegate(&vault_id, &owner);
+    }
+
+    // -------------------------------------------------------------------------
+    // Creation limits
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_limits_reports_max_duration() {
+        let (_env, _cid, client, _admin) = setup();
+
+        assert_eq!(client.get_limits(), (MAX_DURATION, 0i128, 0u32));
+
+        client.set_max_vaults_per_user(&5u32);
+        let (max_duration, _min_amount, max_vaults) = client.get_limits();
+        assert_eq!(max_duration, MAX_DURATION);
+        assert_eq!(max_vaults, 5u32);
+    }
+
+    // -------------------------------------------------------------------------
+    // Idempotent creation
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_create_vault_idempotent_returns_existing_vault() {
+        let (env, _cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+        let external_id = soroban_sdk::BytesN::from_array(&env, &[7u8; 32]);
+        let now = env.ledger().timestamp();
+
+        let first = client.create_vault_idempotent(
+            &external_id, &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        let state_after_first = client.get_contract_state();
+
+        let retried = client.create_vault_idempotent(
+            &external_id, &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(retried, first);
+        assert_eq!(client.get_user_vaults(&owner).len(), 1);
+        assert_eq!(client.get_contract_state(), state_after_first);
+
+        let other = client.create_vault_idempotent(
+            &soroban_sdk::BytesN::from_array(&env, &[8u8; 32]), &owner, &1_000i128, &now,
+            &(now + 1_000), &0i128, &true, &false, &0u64,
+        );
+        assert_ne!(other, first);
+        assert_eq!(client.get_user_vaults(&owner).len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "External id already used with different parameters")]
+    fn test_create_vault_idempotent_rejects_mismatched_retry() {
+        let (env, _cid, client, _admin) = setup();
+        let owner = Address::generate(&env);
+        let external_id = soroban_sdk::BytesN::from_array(&env, &[7u8; 32]);
+        let now = env.ledger().timestamp();
+
+        client.create_vault_idempotent(
+            &external_id, &owner, &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.create_vault_idempotent(
+            &external_id, &owner, &2_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Treasury
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_clawback_sends_tokens_to_treasury() {
+        let (env, cid, client, _admin, token_addr) = setup_with_token();
+        let treasury = Address::generate(&env);
+        let token_client = token::Client::new(&env, &token_addr);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &5_000i128, &(now + 100), &(now + 10_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_treasury(&treasury);
+        assert_eq!(client.get_treasury(), Some(treasury.clone()));
+
+        assert_eq!(client.clawback_vault(&vault_id), 5_000i128);
+        assert_eq!(token_client.balance(&treasury), 5_000i128);
+        assert_eq!(token_client.balance(&cid), 995_000i128);
+        // Routed to the treasury rather than re-credited to the admin pool
+        assert_eq!(client.get_contract_state().2, 995_000i128);
+    }
+
+    #[test]
+    fn test_sweep_expired_sends_tokens_to_treasury() {
+        let (env, _cid, client, _admin, token_addr) = setup_with_token();
+        let treasury = Address::generate(&env);
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &1_000i128, &now, &(now + 1_000),
+            &0i128, &true, &false, &0u64,
+        );
+        client.set_claim_deadline(&vault_id, &(now + 2_000));
+        client.set_treasury(&treasury);
+
+        env.ledger().with_mut(|l| l.timestamp = now + 2_000);
+        assert_eq!(client.sweep_expired(&vault_id), 1_000i128);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&treasury), 1_000i128);
+        assert_eq!(client.get_contract_state().2, 999_000i128);
+    }
+
+    #[test]
+    fn test_clawback_without_treasury_credits_admin_balance() {
+        let (env, _cid, client, _admin) = setup();
+        let now = env.ledger().timestamp();
+
+        let vault_id = client.create_vault_full(
+            &Address::generate(&env), &5_000i128, &(now + 100), &(now + 10_000),
+            &0i128, &true, &false, &0u64,
+        );
+        assert_eq!(client.get_treasury(), None);
+
+        client.clawback_vault(&vault_id);
+        assert_eq!(client.get_contract_state().2, 1_000_000i128);
+    }
+}
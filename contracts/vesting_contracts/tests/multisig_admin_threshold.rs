@@ -108,9 +108,9 @@ fn auth_entry_for_multisig(
 ) -> xdr::SorobanAuthorizationEntry {
     let root_invocation = xdr::SorobanAuthorizedInvocation {
         function: xdr::SorobanAuthorizedFunction::ContractFn(xdr::InvokeContractArgs {
-            contract_address: contract.clone().try_into().unwrap(),
+            contract_address: contract.clone().into(),
             function_name: fn_name.try_into().unwrap(),
-            args: args.try_into().unwrap(),
+            args: args.into(),
         }),
         sub_invocations: std::vec::Vec::<xdr::SorobanAuthorizedInvocation>::new()
             .try_into()
@@ -120,7 +120,7 @@ fn auth_entry_for_multisig(
     xdr::SorobanAuthorizationEntry {
         root_invocation,
         credentials: xdr::SorobanCredentials::Address(xdr::SorobanAddressCredentials {
-            address: authorizer.try_into().unwrap(),
+            address: authorizer.into(),
             nonce,
             signature_expiration_ledger: env.ledger().sequence() + 1000,
             signature,
@@ -131,7 +131,7 @@ fn auth_entry_for_multisig(
 fn signatures_scval(signers: &[Address]) -> xdr::ScVal {
     let mut sig_vals: std::vec::Vec<xdr::ScVal> = std::vec::Vec::with_capacity(signers.len());
     for signer in signers {
-        sig_vals.push(xdr::ScVal::Address(signer.try_into().unwrap()));
+        sig_vals.push(xdr::ScVal::Address(signer.into()));
     }
     xdr::ScVal::Vec(Some(sig_vals.try_into().unwrap()))
 }
@@ -158,7 +158,7 @@ fn create_vault_succeeds_with_multisig_admin_threshold_met() {
     // Vesting contract with multisig as admin.
     let vesting_id = env.register(VestingContract, ());
     let vesting = VestingContractClient::new(&env, &vesting_id);
-    vesting.initialize(&multisig_id, &1_000_000i128);
+    vesting.initialize(&multisig_id, &1_000_000i128, &true, &false);
 
     let beneficiary = Address::generate(&env);
     let now = env.ledger().timestamp();
@@ -225,7 +225,7 @@ fn create_vault_panics_when_multisig_threshold_not_met() {
 
     let vesting_id = env.register(VestingContract, ());
     let vesting = VestingContractClient::new(&env, &vesting_id);
-    vesting.initialize(&multisig_id, &1_000_000i128);
+    vesting.initialize(&multisig_id, &1_000_000i128, &true, &false);
 
     let beneficiary = Address::generate(&env);
     let now = env.ledger().timestamp();
@@ -255,7 +255,7 @@ fn create_vault_panics_when_multisig_threshold_not_met() {
         &vesting_id,
         "create_vault_full",
         args,
-        signatures_scval(&[s1.clone()]),
+        signatures_scval(std::slice::from_ref(&s1)),
         1,
     );
     env.set_auths(&[entry]);
@@ -17,6 +17,17 @@ const START: Symbol        = symbol_short!("START");
 const DURATION: Symbol     = symbol_short!("DURATION");
 const CURVE: Symbol        = symbol_short!("CURVE");
 
+// ---------------------------------------------------------------------------
+// Contract identification
+// ---------------------------------------------------------------------------
+const CONTRACT_VERSION: (u32, u32, u32) = (1, 0, 0);
+const CONTRACT_TYPE: Symbol = symbol_short!("curve");
+
+// ---------------------------------------------------------------------------
+// Fixed-point scale for vesting_rate (tokens per second * RATE_SCALE)
+// ---------------------------------------------------------------------------
+const RATE_SCALE: i128 = 1_000_000;
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum VestingCurve {
@@ -158,6 +169,14 @@ impl VestingVault {
     // View helpers
     // -----------------------------------------------------------------------
 
+    pub fn version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn contract_type(_env: Env) -> Symbol {
+        CONTRACT_TYPE
+    }
+
     pub fn get_curve(env: Env) -> VestingCurve {
         env.storage().instance().get(&CURVE).unwrap()
     }
@@ -174,6 +193,70 @@ impl VestingVault {
         );
         (total, claimed, vested, vested - claimed)
     }
+
+    /// Signed basis-point difference between the configured curve's vested
+    /// fraction and the linear fraction at `at` (negative while behind linear).
+    pub fn compare_to_linear_bps(env: Env, at: u64) -> i32 {
+        let total: i128 = env.storage().instance().get(&TOTAL).unwrap();
+        let start: u64  = env.storage().instance().get(&START).unwrap();
+        let duration: u64 = env.storage().instance().get(&DURATION).unwrap();
+        let curve: VestingCurve = env.storage().instance().get(&CURVE).unwrap();
+
+        let curve_vested  = Self::compute_vested(total, start, duration, at, &curve);
+        let linear_vested = Self::compute_vested(total, start, duration, at, &VestingCurve::Linear);
+
+        ((curve_vested - linear_vested) * 10_000 / total) as i32
+    }
+
+    /// `(linear_vested, exponential_vested)` at `now` for the stored schedule,
+    /// independent of the configured curve.
+    pub fn compare_curves(env: Env, now: u64) -> (i128, i128) {
+        let total: i128 = env.storage().instance().get(&TOTAL).unwrap();
+        let start: u64  = env.storage().instance().get(&START).unwrap();
+        let duration: u64 = env.storage().instance().get(&DURATION).unwrap();
+
+        (
+            Self::compute_vested(total, start, duration, now, &VestingCurve::Linear),
+            Self::compute_vested(total, start, duration, now, &VestingCurve::Exponential),
+        )
+    }
+
+    /// Instantaneous vesting rate at `now` in tokens per second, scaled by
+    /// `RATE_SCALE` (1e6) so small rates don't truncate to zero. Zero outside
+    /// the vesting window.
+    pub fn vesting_rate(env: Env, now: u64) -> i128 {
+        let total: i128 = env.storage().instance().get(&TOTAL).unwrap();
+        let start: u64  = env.storage().instance().get(&START).unwrap();
+        let duration: u64 = env.storage().instance().get(&DURATION).unwrap();
+        let curve: VestingCurve = env.storage().instance().get(&CURVE).unwrap();
+
+        if now < start || now - start >= duration {
+            return 0;
+        }
+
+        let elapsed = now - start;
+
+        match curve {
+            // d/dt (total * t / d) = total / d
+            VestingCurve::Linear => total * RATE_SCALE / duration as i128,
+
+            // d/dt (total * t^2 / d^2) = 2 * total * t / d^2
+            VestingCurve::Exponential => {
+                let scaled_total = (2 * total as u128)
+                    .checked_mul(RATE_SCALE as u128)
+                    .expect("vesting rate overflow");
+                let elapsed_u128  = elapsed as u128;
+                let duration_u128 = duration as u128;
+
+                // scaled_total * elapsed / duration, dividing first so the product can't
+                // overflow; the remainder term stays below duration^2 and keeps it exact
+                let per_duration = (scaled_total / duration_u128) * elapsed_u128
+                    + (scaled_total % duration_u128) * elapsed_u128 / duration_u128;
+
+                i128::try_from(per_duration / duration_u128).expect("vesting rate overflow")
+            }
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
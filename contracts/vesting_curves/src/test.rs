@@ -6,7 +6,7 @@ extern crate std;
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
     token::{Client as TokenClient, StellarAssetClient},
-    Address, Env,
+    Address, Env, Symbol,
 };
 
 use crate::{VestingCurve, VestingVaultClient};
@@ -167,6 +167,99 @@ fn c1_at_midpoint_exponential_less_than_linear() {
     );
 }
 
+#[test]
+fn c2_expo_bps_delta_vs_linear_is_negative() {
+    let s = create_setup(VestingCurve::Exponential);
+
+    // quarter: 6.25% − 25% = −18.75%
+    assert_eq!(s.vault.compare_to_linear_bps(&(START + DURATION / 4)), -1_875);
+    // half: 25% − 50% = −25%
+    assert_eq!(s.vault.compare_to_linear_bps(&(START + DURATION / 2)), -2_500);
+    // three-quarters: 56.25% − 75% = −18.75%
+    assert_eq!(s.vault.compare_to_linear_bps(&(START + (DURATION * 3) / 4)), -1_875);
+    // end: both fully vested
+    assert_eq!(s.vault.compare_to_linear_bps(&(START + DURATION)), 0);
+}
+
+#[test]
+fn c3_linear_bps_delta_vs_linear_is_zero() {
+    let s = create_setup(VestingCurve::Linear);
+    assert_eq!(s.vault.compare_to_linear_bps(&(START + DURATION / 2)), 0);
+}
+
+#[test]
+fn c4_compare_curves_midpoint_and_end() {
+    let s = create_setup(VestingCurve::Linear);
+
+    let (linear_mid, expo_mid) = s.vault.compare_curves(&(START + DURATION / 2));
+    assert_eq!(linear_mid, TOTAL / 2);
+    assert_eq!(expo_mid, TOTAL / 4);
+    assert!(expo_mid < linear_mid);
+
+    assert_eq!(s.vault.compare_curves(&(START + DURATION)), (TOTAL, TOTAL));
+}
+
+// ── Vesting rate ────────────────────────────────────────────────────────────
+
+const RATE_SCALE: i128 = 1_000_000;
+
+#[test]
+fn r1_linear_rate_is_constant() {
+    let s = create_setup(VestingCurve::Linear);
+    let expected = TOTAL * RATE_SCALE / DURATION as i128;
+
+    assert_eq!(s.vault.vesting_rate(&START), expected);
+    assert_eq!(s.vault.vesting_rate(&(START + DURATION / 2)), expected);
+    assert_eq!(s.vault.vesting_rate(&(START + DURATION - 1)), expected);
+}
+
+#[test]
+fn r2_expo_rate_grows_with_elapsed() {
+    let s = create_setup(VestingCurve::Exponential);
+
+    let quarter = s.vault.vesting_rate(&(START + DURATION / 4));
+    let half    = s.vault.vesting_rate(&(START + DURATION / 2));
+    let late    = s.vault.vesting_rate(&(START + (DURATION * 3) / 4));
+
+    assert_eq!(s.vault.vesting_rate(&START), 0);
+    assert!(quarter < half && half < late);
+    // At the midpoint the exponential rate equals the linear rate: 2 * T * (d/2) / d^2 = T / d
+    assert_eq!(half, TOTAL * RATE_SCALE / DURATION as i128);
+}
+
+#[test]
+fn r3_rate_is_zero_outside_window() {
+    let s = create_setup(VestingCurve::Linear);
+    assert_eq!(s.vault.vesting_rate(&(START - 1)), 0);
+    assert_eq!(s.vault.vesting_rate(&(START + DURATION)), 0);
+}
+
+#[test]
+fn r4_expo_rate_large_total_long_duration() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // 2 * total * elapsed * RATE_SCALE would overflow u128 if multiplied out first
+    let total: i128   = 1_000_000_000_000_000_000_000_000_000_000_i128;
+    let duration: u64 = 10 * 365 * 24 * 60 * 60;
+
+    let vault = VestingVaultClient::new(&env, &env.register(crate::VestingVault, ()));
+    vault.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &total,
+        &START,
+        &duration,
+        &VestingCurve::Exponential,
+    );
+
+    assert_eq!(
+        vault.vesting_rate(&(START + duration / 2)),
+        total * RATE_SCALE / duration as i128
+    );
+}
+
 // ── Integration tests ────────────────────────────────────────────────────────
 
 #[test]
@@ -257,6 +350,13 @@ fn i6_double_claim_only_yields_incremental_amount() {
     assert_eq!(bal, TOTAL);
 }
 
+#[test]
+fn i7_version_and_contract_type() {
+    let s = create_setup(VestingCurve::Linear);
+    assert_eq!(s.vault.contract_type(), Symbol::new(&s.env, "curve"));
+    assert_eq!(s.vault.version(), (1, 0, 0));
+}
+
 // ── Zero-duration / zero-amount edge cases (Issue #41) ──────────────────────
 
 #[test]
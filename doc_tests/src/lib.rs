@@ -57,6 +57,41 @@ pub fn extract_urls(content: &str) -> Vec<String> {
         .collect()
 }
 
+/// Helper function to check that sections appear in the given relative order
+/// Other sections may appear in between; a missing section fails the check
+pub fn sections_in_order(content: &str, ordered: &[&str]) -> bool {
+    let headers: Vec<&str> = content
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#').trim())
+        .collect();
+
+    let mut next_index = 0;
+    for section_name in ordered {
+        match headers[next_index..].iter().position(|h| h == section_name) {
+            Some(offset) => next_index += offset + 1,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Helper function to extract inline markdown links as (text, target) pairs
+/// Reference-style links such as [text][ref] are not matched
+pub fn extract_markdown_links(content: &str) -> Vec<(String, String)> {
+    let link_pattern = Regex::new(r"\[([^\]]*)\]\(([^)\s]+)\)").unwrap();
+    link_pattern
+        .captures_iter(content)
+        .map(|c| (c[1].to_string(), c[2].to_string()))
+        .collect()
+}
+
+/// Helper function to check if a link target points into the repository
+/// Absolute URLs (any scheme), mailto: links and same-page anchors are not relative
+pub fn is_relative_link(target: &str) -> bool {
+    !(target.contains("://") || target.starts_with("mailto:") || target.starts_with('#'))
+}
+
 /// Helper function to check if a section exists in the content
 pub fn section_exists(content: &str, section_name: &str) -> bool {
     let lines: Vec<&str> = content.lines().collect();
@@ -71,6 +106,36 @@ pub fn section_exists(content: &str, section_name: &str) -> bool {
     false
 }
 
+/// Helper function to extract fenced code blocks from markdown content
+/// Returns (language, code) pairs; untagged fences yield an empty language
+pub fn extract_code_blocks(content: &str) -> Vec<(String, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            match current.take() {
+                // Closing fence: finish the current block
+                Some(block) => blocks.push(block),
+                // Opening fence: anything after the backticks is the language tag
+                None => {
+                    let language = trimmed.trim_start_matches('`').trim().to_string();
+                    current = Some((language, String::new()));
+                }
+            }
+            continue;
+        }
+
+        if let Some((_, code)) = current.as_mut() {
+            code.push_str(line);
+            code.push('\n');
+        }
+    }
+
+    blocks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +173,71 @@ mod tests {
         assert!(section_exists(content, "Subsection"));
         assert!(!section_exists(content, "Missing Section"));
     }
+
+    #[test]
+    fn test_extract_code_blocks_rust() {
+        let content = "Intro\n```rust\nfn main() {\n\n    run();\n}\n```\nOutro";
+        let blocks = extract_code_blocks(content);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].0, "rust");
+        assert_eq!(blocks[0].1, "fn main() {\n\n    run();\n}\n");
+    }
+
+    #[test]
+    fn test_extract_code_blocks_untagged() {
+        let content = "```\nstellar contract build\n```";
+        let blocks = extract_code_blocks(content);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].0, "");
+        assert_eq!(blocks[0].1, "stellar contract build\n");
+    }
+
+    #[test]
+    fn test_extract_code_blocks_ignores_inline_code() {
+        let content = "Call `revoke_tokens` before `claim_tokens` runs.";
+        assert!(extract_code_blocks(content).is_empty());
+    }
+
+    #[test]
+    fn test_extract_markdown_links_absolute() {
+        let content = "See [Stellar](https://stellar.org) for details";
+        let links = extract_markdown_links(content);
+        assert_eq!(links, vec![("Stellar".to_string(), "https://stellar.org".to_string())]);
+        assert!(!is_relative_link(&links[0].1));
+    }
+
+    #[test]
+    fn test_extract_markdown_links_relative() {
+        let content = "Read [the policy](./SECURITY.md) and [spec](SPEC.md#limits)";
+        let links = extract_markdown_links(content);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0], ("the policy".to_string(), "./SECURITY.md".to_string()));
+        assert!(is_relative_link(&links[0].1));
+        assert!(is_relative_link(&links[1].1));
+        assert!(!is_relative_link("#overview"));
+    }
+
+    #[test]
+    fn test_extract_markdown_links_ignores_reference_style() {
+        let content = "See [the spec][spec]\n\n[spec]: ./SPEC.md";
+        assert!(extract_markdown_links(content).is_empty());
+    }
+
+    #[test]
+    fn test_sections_in_order() {
+        let content = "# Title\n## Overview\n## Scope\n## Known Limitations\n## References";
+        assert!(sections_in_order(content, &["Overview", "Known Limitations", "References"]));
+    }
+
+    #[test]
+    fn test_sections_out_of_order() {
+        let content = "# Title\n## Known Limitations\n## Overview\n## References";
+        assert!(!sections_in_order(content, &["Overview", "Known Limitations", "References"]));
+    }
+
+    #[test]
+    fn test_sections_in_order_missing_section() {
+        let content = "# Title\n## Overview\n## References";
+        assert!(!sections_in_order(content, &["Overview", "Known Limitations", "References"]));
+    }
 }